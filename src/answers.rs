@@ -0,0 +1,160 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use crate::parse::{lines, ParseError};
+use crate::solution::{Answer, Part};
+
+#[derive(Error, Debug)]
+pub enum AnswersError {
+    #[error("unable to access {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}: {source}", .path.display())]
+    Parse { path: PathBuf, source: ParseError },
+}
+
+/// The known-correct answers for one input, kept next to it as `dayN.answers`:
+///
+/// ```text
+/// part1 = 56108
+/// part2 = 55652
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Answers {
+    part1: Option<Answer>,
+    part2: Option<Answer>,
+}
+
+impl Answers {
+    pub fn get(&self, part: Part) -> Option<Answer> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+    }
+
+    pub fn set(&mut self, part: Part, answer: Answer) {
+        match part {
+            Part::One => self.part1 = Some(answer),
+            Part::Two => self.part2 = Some(answer),
+        }
+    }
+
+    /// Answers recorded for `input`. Nothing recorded yet is not an error.
+    pub fn load(input: &Path) -> Result<Answers, AnswersError> {
+        let path = answers_path(input);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Answers::default()),
+            Err(source) => return Err(AnswersError::Io { path, source }),
+        };
+        text.parse()
+            .map_err(|source| AnswersError::Parse { path, source })
+    }
+
+    pub fn save(&self, input: &Path) -> Result<(), AnswersError> {
+        let path = answers_path(input);
+        fs::write(&path, self.to_string())
+            .map_err(|source| AnswersError::Io { path, source })
+    }
+}
+
+impl FromStr for Answers {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut answers = Answers::default();
+        for line in lines(text) {
+            let entry = line.text.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(entry, "=", "\"partN = <answer>\"")?;
+            let part = match key.trim() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                _ => return Err(line.error(key, "part1 or part2")),
+            };
+            answers.set(part, line.parse(value.trim(), "an answer")?);
+        }
+        Ok(answers)
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in Part::ALL {
+            if let Some(answer) = self.get(part) {
+                writeln!(f, "part{part} = {answer}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `inputs/day5.txt` has its answers in `inputs/day5.answers`.
+pub fn answers_path(input: &Path) -> PathBuf {
+    input.with_extension("answers")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail(String),
+    /// Nothing recorded to compare against.
+    Unknown(Answer),
+    /// The part isn't run, see [`crate::solution::Solution::part2`].
+    Skipped,
+}
+
+impl Status {
+    pub fn check(expected: Option<Answer>, actual: Option<Answer>) -> Self {
+        match (expected, actual) {
+            (_, None) => Status::Skipped,
+            (None, Some(actual)) => Status::Unknown(actual),
+            (Some(expected), Some(actual)) if expected == actual => Status::Pass,
+            (Some(expected), Some(actual)) => Status::Fail(format!("expected {expected}, got {actual}")),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail(reason) => write!(f, "FAIL {reason}"),
+            Status::Unknown(actual) => write!(f, "unknown, got {actual}"),
+            Status::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+    use crate::answers::{answers_path, Answers, Status};
+    use crate::solution::Part;
+
+    #[test]
+    fn test_format() {
+        let answers: Answers = "# day 5\npart1 = 35\n\npart2=46\n".parse().unwrap();
+        assert_eq!(answers.get(Part::One), Some(35));
+        assert_eq!(answers.get(Part::Two), Some(46));
+        assert_eq!(answers.to_string(), "part1 = 35\npart2 = 46\n");
+
+        let err = "part3 = 1".parse::<Answers>().unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 1: expected part1 or part2, found \"part3 \"");
+
+        assert_eq!(answers_path(Path::new("inputs/day1_test.txt")), Path::new("inputs/day1_test.answers"));
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(Status::check(Some(4), Some(4)), Status::Pass);
+        assert_eq!(Status::check(Some(4), Some(5)), Status::Fail(String::from("expected 4, got 5")));
+        assert_eq!(Status::check(None, Some(5)), Status::Unknown(5));
+        assert_eq!(Status::check(Some(4), None), Status::Skipped);
+    }
+}
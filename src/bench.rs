@@ -0,0 +1,276 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::hint::black_box;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::solution::{Part, Runner};
+
+/// Where results are kept between runs so the next one has something to compare against.
+pub const DEFAULT_BENCH_FILE: &str = "bench.json";
+
+/// How many times each stage runs unless `--iterations` says otherwise.
+pub const DEFAULT_ITERATIONS: u32 = 10;
+
+/// How many percent slower than the previous run a stage can get before it's flagged.
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+#[derive(Error, Debug)]
+pub enum BenchError {
+    #[error("unable to access {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{} is not a benchmark file: {source}", .path.display())]
+    Format { path: PathBuf, source: serde_json::Error },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl From<Part> for Stage {
+    fn from(part: Part) -> Self {
+        match part {
+            Part::One => Stage::Part1,
+            Part::Two => Stage::Part2,
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part 1"),
+            Stage::Part2 => write!(f, "part 2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Timing {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "at least one sample is needed");
+        samples.sort();
+        let ns = |d: Duration| d.as_nanos().try_into().unwrap_or(u64::MAX);
+        Timing {
+            min_ns: ns(samples[0]),
+            median_ns: ns(samples[samples.len() / 2]),
+            max_ns: ns(samples[samples.len() - 1]),
+        }
+    }
+
+    pub fn median(&self) -> Duration {
+        Duration::from_nanos(self.median_ns)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub day: u32,
+    pub stage: Stage,
+    /// What was timed: `puzzle`, `example`, `stdin` or the path given with `--input`. Timings are only
+    /// compared with others of the same input.
+    #[serde(default)]
+    pub input: String,
+    #[serde(flatten)]
+    pub timing: Timing,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub iterations: u32,
+    pub measurements: Vec<Measurement>,
+}
+
+impl Report {
+    /// The report saved by the last run, if there was one.
+    pub fn load(path: &Path) -> Result<Option<Report>, BenchError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(BenchError::Io { path: path.to_path_buf(), source }),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|source| BenchError::Format { path: path.to_path_buf(), source })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BenchError> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|source| BenchError::Format { path: path.to_path_buf(), source })?;
+        fs::write(path, text + "\n")
+            .map_err(|source| BenchError::Io { path: path.to_path_buf(), source })
+    }
+
+    pub fn get(&self, day: u32, stage: Stage, input: &str) -> Option<&Timing> {
+        self.measurements.iter()
+            .find(|m| m.day == day && m.stage == stage && m.input == input)
+            .map(|m| &m.timing)
+    }
+
+    /// Keeps the measurements of stages and inputs that weren't benchmarked this time, so `--day` and
+    /// `--part` don't forget the rest. Measurements saved before inputs were recorded are dropped, as
+    /// nothing can be compared with them.
+    pub fn merge(&mut self, previous: &Report) {
+        for m in &previous.measurements {
+            let replaced = self.measurements.iter().any(|n| n.day == m.day && n.stage == m.stage && n.input == m.input);
+            if !replaced && !m.input.is_empty() {
+                self.measurements.push(m.clone());
+            }
+        }
+        self.measurements.sort_by(|a, b| (a.day, a.stage, &a.input).cmp(&(b.day, b.stage, &b.input)));
+    }
+}
+
+/// Runs `f` the given number of times, bailing out on the first error.
+pub fn time<T, E>(iterations: u32, mut f: impl FnMut() -> Result<T, E>) -> Result<Timing, E> {
+    let mut samples = Vec::with_capacity(iterations as usize);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        black_box(f()?);
+        samples.push(start.elapsed());
+    }
+    Ok(Timing::from_samples(samples))
+}
+
+/// Times parsing and every requested part of a day on `source`, as [`Measurement::input`] names it. Parts
+/// that aren't run, like day 5 part 2, are left out.
+pub fn bench_day(day: &dyn Runner, texts: &[(Part, String)], source: &str, iterations: u32) -> Result<Vec<Measurement>, Box<dyn Error>> {
+    let mut measurements = vec!();
+    let Some((_, first)) = texts.first() else {
+        return Ok(measurements);
+    };
+
+    let timing = time(iterations, || day.parse(first))?;
+    measurements.push(Measurement { day: day.day(), stage: Stage::Parse, input: String::from(source), timing });
+
+    for (part, text) in texts {
+        let input = day.parse(text)?;
+        if day.solve(input.as_ref(), *part)?.is_none() {
+            continue;
+        }
+        let timing = time(iterations, || day.solve(input.as_ref(), *part))?;
+        measurements.push(Measurement { day: day.day(), stage: (*part).into(), input: String::from(source), timing });
+    }
+
+    Ok(measurements)
+}
+
+/// Whether `current` is more than `threshold` percent slower than `previous`.
+pub fn is_regression(current: &Timing, previous: &Timing, threshold: f64) -> bool {
+    current.median_ns as f64 > previous.median_ns as f64 * (1.0 + threshold / 100.0)
+}
+
+/// Prints the table and returns how many stages got slower.
+pub fn print_table(report: &Report, previous: Option<&Report>, threshold: f64) -> usize {
+    let mut regressions = 0;
+    println!("{:>3}  {:<6}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}",
+             "Day", "Stage", "Min", "Median", "Max", "Previous", "Change");
+
+    for m in &report.measurements {
+        let previous = previous.and_then(|p| p.get(m.day, m.stage, &m.input));
+        let (prev, change, flag) = match previous {
+            Some(prev) => {
+                let change = (m.timing.median_ns as f64 / prev.median_ns.max(1) as f64 - 1.0) * 100.0;
+                let flag = if is_regression(&m.timing, prev, threshold) {
+                    regressions += 1;
+                    "  REGRESSION"
+                } else {
+                    ""
+                };
+                (format!("{:.2?}", prev.median()), format!("{change:+.1}%"), flag)
+            }
+            None => (String::from("-"), String::from("-"), ""),
+        };
+
+        println!("{:>3}  {:<6}  {:>12}  {:>12}  {:>12}  {:>12}  {:>8}{}",
+                 m.day,
+                 m.stage.to_string(),
+                 format!("{:.2?}", Duration::from_nanos(m.timing.min_ns)),
+                 format!("{:.2?}", m.timing.median()),
+                 format!("{:.2?}", Duration::from_nanos(m.timing.max_ns)),
+                 prev,
+                 change,
+                 flag);
+    }
+
+    regressions
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+    use crate::bench::{is_regression, time, Measurement, Report, Stage, Timing};
+
+    fn timing(median_ns: u64) -> Timing {
+        Timing { min_ns: median_ns, median_ns, max_ns: median_ns }
+    }
+
+    #[test]
+    fn test_stats() {
+        let samples = [5, 1, 4, 2, 3].map(Duration::from_nanos).to_vec();
+        assert_eq!(Timing::from_samples(samples), Timing { min_ns: 1, median_ns: 3, max_ns: 5 });
+
+        let mut runs = 0;
+        time(4, || { runs += 1; Ok::<_, ()>(runs) }).unwrap();
+        assert_eq!(runs, 4);
+        assert_eq!(time(4, || Err::<(), _>("broken")), Err("broken"));
+    }
+
+    fn measurement(day: u32, stage: Stage, input: &str, median_ns: u64) -> Measurement {
+        Measurement { day, stage, input: String::from(input), timing: timing(median_ns) }
+    }
+
+    #[test]
+    fn test_compare() {
+        assert!(is_regression(&timing(120), &timing(100), 10.0));
+        assert!(!is_regression(&timing(105), &timing(100), 10.0));
+
+        let mut report = Report {
+            iterations: 3,
+            measurements: vec![
+                measurement(2, Stage::Parse, "puzzle", 10),
+                measurement(2, Stage::Part1, "puzzle", 30),
+            ],
+        };
+        let previous = Report {
+            iterations: 3,
+            measurements: vec![
+                measurement(1, Stage::Part1, "puzzle", 7),
+                measurement(2, Stage::Parse, "puzzle", 20),
+                measurement(2, Stage::Part2, "puzzle", 40),
+                measurement(2, Stage::Parse, "example", 2),
+                measurement(3, Stage::Parse, "", 5),
+            ],
+        };
+        report.merge(&previous);
+
+        let text = serde_json::to_string(&report).unwrap();
+        let report: Report = serde_json::from_str(&text).unwrap();
+        assert_eq!(report.get(1, Stage::Part1, "puzzle"), Some(&timing(7)));
+        assert_eq!(report.get(2, Stage::Parse, "puzzle"), Some(&timing(10)));
+        /* A run of part 1 keeps part 2, and the example is kept apart from the puzzle */
+        assert_eq!(report.get(2, Stage::Part2, "puzzle"), Some(&timing(40)));
+        assert_eq!(report.get(2, Stage::Parse, "example"), Some(&timing(2)));
+        assert_eq!(report.get(1, Stage::Part1, "example"), None);
+        assert_eq!(report.measurements.len(), 5);
+
+        let old: Report = serde_json::from_str(r#"{"iterations": 3, "measurements": [
+            {"day": 1, "stage": "parse", "min_ns": 1, "median_ns": 2, "max_ns": 3}
+        ]}"#).unwrap();
+        assert_eq!(old.measurements[0].input, "");
+    }
+}
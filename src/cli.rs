@@ -0,0 +1,400 @@
+use std::path::PathBuf;
+use clap::{value_parser, ArgGroup, Args, Parser, Subcommand};
+use crate::bench::{DEFAULT_BENCH_FILE, DEFAULT_ITERATIONS, DEFAULT_THRESHOLD};
+use crate::day1::MissingDigits;
+use crate::day2::Bag;
+use crate::day3::{GearRule, GraphFormat, Render};
+use crate::input::{DEFAULT_INPUTS, INPUTS_ENV};
+use crate::output::Format;
+use crate::solution::Part;
+
+/// Advent of Code 2023 solutions.
+#[derive(Parser, Debug)]
+#[command(name = "aoc")]
+pub struct Cli {
+    /// Runs every day on its puzzle input when left out.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Directory holding the `dayN.txt` puzzle inputs and `dayN_test.txt` examples.
+    #[arg(long, global = true, env = INPUTS_ENV, default_value = DEFAULT_INPUTS)]
+    pub inputs: PathBuf,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Solve a single day, or all of them.
+    Run(RunArgs),
+    /// Check every day against its recorded answers.
+    Verify(VerifyArgs),
+    /// Add a new day with empty inputs, ready to be solved.
+    NewDay(NewDayArgs),
+    /// Day 1's calibration, reading digits spelled out in another vocabulary.
+    Day1(Day1Args),
+    /// Day 2's cube games, played with another bag.
+    Day2(Day2Args),
+    /// Day 3's engine schematic, drawn to show which numbers are parts and gears.
+    Day3(Day3Args),
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("days").required(true).args(["day", "all"])))]
+pub struct RunArgs {
+    /// Day to solve.
+    #[arg(short, long)]
+    pub day: Option<u32>,
+
+    /// Solve every registered day.
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Only solve this part.
+    #[arg(short, long)]
+    pub part: Option<Part>,
+
+    /// Read the puzzle input from this file, or from stdin when given `-`.
+    #[arg(short, long, conflicts_with_all = ["all", "test"])]
+    pub input: Option<PathBuf>,
+
+    /// Use the `dayN_test.txt` example instead of the puzzle input.
+    #[arg(short, long)]
+    pub test: bool,
+
+    /// How to print the results.
+    #[arg(short, long, value_enum, default_value_t, conflicts_with = "bench")]
+    pub format: Format,
+
+    /// Solve the days and their parts on a pool of threads.
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// Number of threads for `--parallel`, the number of CPUs by default.
+    #[arg(long, requires = "parallel", value_parser = value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Time parsing and each part instead of printing the answers.
+    #[arg(long, conflicts_with = "parallel")]
+    pub bench: bool,
+
+    /// How many times each stage runs when benchmarking.
+    #[arg(long, default_value_t = DEFAULT_ITERATIONS, requires = "bench", value_parser = value_parser!(u32).range(1..))]
+    pub iterations: u32,
+
+    /// Benchmark results are compared against and then saved to this file.
+    #[arg(long, default_value = DEFAULT_BENCH_FILE, requires = "bench")]
+    pub bench_file: PathBuf,
+
+    /// Flag a stage whose median is this many percent slower than the previous run.
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD, requires = "bench")]
+    pub threshold: f64,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Only verify this day.
+    #[arg(short, long)]
+    pub day: Option<u32>,
+
+    /// Check the examples instead of the puzzle inputs.
+    #[arg(short, long)]
+    pub test: bool,
+
+    /// Save the answers of inputs that have none recorded yet.
+    #[arg(long)]
+    pub record: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct NewDayArgs {
+    /// Day to add.
+    #[arg(value_parser = value_parser!(u32).range(1..=25))]
+    pub day: u32,
+
+    /// Root of the crate to add the day to.
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+/// Where a day-specific command reads its input from.
+#[derive(Args, Debug)]
+pub struct DayInput {
+    /// Read the puzzle input from this file, or from stdin when given `-`.
+    #[arg(short, long, conflicts_with = "test")]
+    pub input: Option<PathBuf>,
+
+    /// Use the day's example instead of the puzzle input.
+    #[arg(short, long)]
+    pub test: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Day1Args {
+    #[command(flatten)]
+    pub input: DayInput,
+
+    /// Words read as digits: digits, english, french, german, spanish, roman, or a `.toml` or
+    /// `.json` file mapping words to digits.
+    #[arg(short, long, default_value = "english")]
+    pub vocabulary: String,
+
+    /// What to do with lines that have no digits.
+    #[arg(long, value_enum, default_value_t)]
+    pub no_digits: MissingDigits,
+
+    /// Show the digits found on every line, which were used and the value they make.
+    #[arg(short, long)]
+    pub explain: bool,
+
+    /// Only show the lines that part 1, reading only digits, reads differently.
+    #[arg(long)]
+    pub diff: bool,
+}
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("generated").args(["simulate", "bench_parsers"])))]
+pub struct Day2Args {
+    #[command(flatten)]
+    pub input: DayInput,
+
+    /// The cubes in the bag, e.g. "12 red, 13 green, 14 blue, 3 purple".
+    #[arg(short, long, default_value_t = Bag::standard(), conflicts_with = "bag_file")]
+    pub bag: Bag,
+
+    /// Read the bag from a `.toml` or `.json` file mapping colours to counts.
+    #[arg(long)]
+    pub bag_file: Option<PathBuf>,
+
+    /// List the impossible games with each draw and colour the bag doesn't have enough cubes for.
+    #[arg(short, long)]
+    pub rejected: bool,
+
+    /// Estimate the bag behind each game, and behind all of them together, from the draws.
+    #[arg(long)]
+    pub infer: bool,
+
+    /// How sure the estimated ranges are, between 0 and 1.
+    #[arg(long, default_value_t = 0.95, value_parser = parse_confidence)]
+    pub confidence: f64,
+
+    /// Find the bag with the fewest cubes that allows at least this many games.
+    #[arg(long, value_name = "GAMES")]
+    pub smallest_bag: Option<usize>,
+
+    /// Find the bag of at most this many cubes whose games have the largest sum of ids.
+    #[arg(long, value_name = "CUBES")]
+    pub budget: Option<u64>,
+
+    /// List the bags that no other bag beats on both fewer cubes and a larger sum of game ids.
+    #[arg(long)]
+    pub pareto: bool,
+
+    /// Print this many games made up from the bag instead of solving.
+    #[arg(long, value_name = "GAMES")]
+    pub simulate: Option<u32>,
+
+    /// Time the regex game parser against the single-pass one on this many made up games.
+    #[arg(long, value_name = "GAMES")]
+    pub bench_parsers: Option<u32>,
+
+    /// How many times each parser runs with `--bench-parsers`.
+    #[arg(long, default_value_t = 10, requires = "bench_parsers", value_parser = value_parser!(u32).range(1..))]
+    pub iterations: u32,
+
+    /// Seed of the made up games.
+    #[arg(long, default_value_t = 0, requires = "generated")]
+    pub seed: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct Day3Args {
+    #[command(flatten)]
+    pub input: DayInput,
+
+    /// Draw the schematic with parts, other numbers, symbols and gears told apart, instead of solving.
+    #[arg(short, long, value_enum)]
+    pub render: Option<Render>,
+
+    /// Which symbols are gears and what they're worth: a symbol or `any`, how many parts (`2`, or `2+`
+    /// for at least two) and `product` or `sum`, e.g. "# 3 product".
+    #[arg(short, long, default_value_t = GearRule::default())]
+    pub gear_rule: GearRule,
+
+    /// List the symbols grouped by how many parts they touch.
+    #[arg(short, long)]
+    pub symbols: bool,
+
+    /// Solve a row at a time without reading the whole schematic, for very large ones.
+    #[arg(long, conflicts_with_all = ["render", "gear_rule", "symbols"])]
+    pub stream: bool,
+
+    /// Write the graph of numbers joined to the symbols they touch, instead of solving.
+    #[arg(long, value_enum, conflicts_with_all = ["render", "stream", "symbols", "components"])]
+    pub graph: Option<GraphFormat>,
+
+    /// List the groups of parts joined through shared symbols, and the symbol touching the most parts.
+    #[arg(long, conflicts_with = "stream")]
+    pub components: bool,
+}
+
+fn parse_confidence(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(confidence) if confidence > 0.0 && confidence < 1.0 => Ok(confidence),
+        _ => Err(String::from("expected a number between 0 and 1")),
+    }
+}
+
+impl RunArgs {
+    /// Solve every day on the real input, which is what running without a subcommand does.
+    pub fn all() -> Self {
+        RunArgs {
+            day: None,
+            all: true,
+            part: None,
+            input: None,
+            test: false,
+            format: Format::Text,
+            parallel: false,
+            threads: None,
+            bench: false,
+            iterations: DEFAULT_ITERATIONS,
+            bench_file: PathBuf::from(DEFAULT_BENCH_FILE),
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+
+    pub fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => Part::ALL.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::{CommandFactory, Parser};
+    use crate::cli::{Cli, Command, RunArgs};
+    use crate::day1::MissingDigits;
+    use crate::day2::Bag;
+    use crate::day3::{GearRule, Render};
+    use crate::output::Format;
+    use crate::solution::Part;
+
+    #[test]
+    fn test_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_run() {
+        let cli = Cli::try_parse_from(["aoc", "run", "--day", "5", "--part", "2", "--input", "-"]).unwrap();
+        let Some(Command::Run(args)) = cli.command else {
+            panic!("expected the run command");
+        };
+        assert_eq!(args.day, Some(5));
+        assert_eq!(args.parts(), vec![Part::Two]);
+        assert_eq!(args.input.unwrap().to_str(), Some("-"));
+
+        let cli = Cli::try_parse_from(["aoc", "run", "--all", "--inputs", "elsewhere"]).unwrap();
+        assert_eq!(cli.inputs.to_str(), Some("elsewhere"));
+
+        assert!(Cli::try_parse_from(["aoc", "run"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--input", "day1.txt"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--day", "1", "--part", "3"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--bench", "--iterations", "0"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--threads", "2"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--bench", "--format", "json"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--bench", "--parallel"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--bench", "--iterations", "50", "--threshold", "5"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--iterations", "50"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--bench-file", "other.json"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "run", "--all", "--threshold", "5"]).is_err());
+
+        let cli = Cli::try_parse_from(["aoc", "run", "--all", "--format", "csv"]).unwrap();
+        let Some(Command::Run(args)) = cli.command else {
+            panic!("expected the run command");
+        };
+        assert_eq!(args.format, Format::Csv);
+        let all = RunArgs::all();
+        assert_eq!((args.iterations, &args.bench_file, args.threshold), (all.iterations, &all.bench_file, all.threshold));
+    }
+
+    #[test]
+    fn test_new_day() {
+        let cli = Cli::try_parse_from(["aoc", "new-day", "9"]).unwrap();
+        let Some(Command::NewDay(args)) = cli.command else {
+            panic!("expected the new-day command");
+        };
+        assert_eq!(args.day, 9);
+        assert_eq!(args.root.to_str(), Some("."));
+
+        assert!(Cli::try_parse_from(["aoc", "new-day", "26"]).is_err());
+    }
+
+    #[test]
+    fn test_day1() {
+        let cli = Cli::try_parse_from(["aoc", "day1", "--vocabulary", "roman", "--test"]).unwrap();
+        let Some(Command::Day1(args)) = cli.command else {
+            panic!("expected the day1 command");
+        };
+        assert_eq!(args.vocabulary, "roman");
+        assert!(args.input.test);
+
+        assert!(!args.explain && !args.diff);
+        assert_eq!(args.no_digits, MissingDigits::Error);
+
+        let cli = Cli::try_parse_from(["aoc", "day1", "--no-digits", "skip"]).unwrap();
+        let Some(Command::Day1(args)) = cli.command else {
+            panic!("expected the day1 command");
+        };
+        assert_eq!(args.no_digits, MissingDigits::Skip);
+
+        assert!(Cli::try_parse_from(["aoc", "day1", "--test", "--input", "-"]).is_err());
+    }
+
+    #[test]
+    fn test_day2() {
+        let cli = Cli::try_parse_from(["aoc", "day2", "--bag", "1 red, 2 purple"]).unwrap();
+        let Some(Command::Day2(args)) = cli.command else {
+            panic!("expected the day2 command");
+        };
+        assert_eq!(args.bag, Bag::new([("red", 1), ("purple", 2)]));
+        assert!(!args.rejected);
+        assert!(!args.pareto);
+        assert_eq!(args.confidence, 0.95);
+
+        assert!(Cli::try_parse_from(["aoc", "day2", "--infer", "--confidence", "1"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day2", "--seed", "1"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day2", "--smallest-bag", "3", "--budget", "20", "--pareto"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "day2", "--bench-parsers", "100", "--seed", "1"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "day2", "--simulate", "1", "--bench-parsers", "1"]).is_err());
+
+        assert!(Cli::try_parse_from(["aoc", "day2", "--bag", "1 red, purple"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day2", "--bag", "1 red", "--bag-file", "bag.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_day3() {
+        let cli = Cli::try_parse_from(["aoc", "day3", "--render", "html", "--test"]).unwrap();
+        let Some(Command::Day3(args)) = cli.command else {
+            panic!("expected the day3 command");
+        };
+        assert_eq!(args.render, Some(Render::Html));
+        assert_eq!(args.gear_rule, GearRule::default());
+
+        let cli = Cli::try_parse_from(["aoc", "day3", "--gear-rule", "any 2+ sum"]).unwrap();
+        let Some(Command::Day3(args)) = cli.command else {
+            panic!("expected the day3 command");
+        };
+        assert_eq!(args.gear_rule.to_string(), "any 2+ sum");
+        assert!(Cli::try_parse_from(["aoc", "day3", "--gear-rule", "* 2"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--stream", "--input", "big.txt"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--stream", "--render", "ansi"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--graph", "dot", "--input", "big.txt"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--graph", "dot", "--components"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--graph", "json", "--symbols"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--symbols", "--components"]).is_ok());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--graph", "dot", "--render", "html"]).is_err());
+        assert!(Cli::try_parse_from(["aoc", "day3", "--render", "svg"]).is_err());
+    }
+}
@@ -0,0 +1,433 @@
+use std::error::Error;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+use crate::answers::{Answers, Status};
+use crate::bench::{self, Report};
+use crate::cli::{Day1Args, Day2Args, Day3Args, DayInput, NewDayArgs, RunArgs, VerifyArgs};
+use crate::day1::{self, Vocabulary};
+use crate::day2::{self, Bag, Day2, Simulator};
+use crate::day3::{self, Day3, PartGraph};
+use crate::input::{open_file, read_file, Inputs};
+use crate::output::{self, Outcome};
+use crate::parallel;
+use crate::scaffold;
+use crate::solution::{Part, Registry, Runner, Solution};
+
+fn read_input(inputs: &Inputs, day: &dyn Runner, part: Part, args: &RunArgs, stdin: &Option<String>) -> Result<String, Box<dyn Error>> {
+    if let Some(text) = stdin {
+        return Ok(text.clone());
+    }
+
+    let text = match &args.input {
+        Some(path) => read_file(path)?,
+        None if args.test => inputs.example(day.day(), part)?,
+        None => inputs.puzzle(day.day())?,
+    };
+    Ok(text)
+}
+
+fn selected_days<'a>(registry: &'a Registry, args: &RunArgs) -> Result<Vec<&'a dyn Runner>, Box<dyn Error>> {
+    Ok(match args.day {
+        Some(n) => vec![registry.get(n).ok_or(format!("day {n} has no solution"))?],
+        None => registry.days().collect(),
+    })
+}
+
+fn read_stdin(args: &RunArgs) -> Result<Option<String>, Box<dyn Error>> {
+    match &args.input {
+        Some(path) if path.as_os_str() == "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            Ok(Some(text))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The input of a day-specific command, the example being the one for `part`.
+fn read_day_input(inputs: &Inputs, day: u32, part: Part, args: &DayInput) -> Result<String, Box<dyn Error>> {
+    let text = match &args.input {
+        Some(path) if path.as_os_str() == "-" => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        Some(path) => read_file(path)?,
+        None if args.test => inputs.example(day, part)?,
+        None => inputs.puzzle(day)?,
+    };
+    Ok(text)
+}
+
+/// The input for each requested part of a day.
+fn read_inputs(inputs: &Inputs, day: &dyn Runner, args: &RunArgs, stdin: &Option<String>) -> Result<Vec<(Part, String)>, Box<dyn Error>> {
+    args.parts()
+        .into_iter()
+        .map(|part| Ok((part, read_input(inputs, day, part, args, stdin)?)))
+        .collect()
+}
+
+/// Solves each part on its own input, only parsing again when a part has its own example.
+fn solve_day(day: &dyn Runner, texts: Vec<(Part, String)>) -> Vec<Outcome> {
+    let mut parsed = None;
+    texts.into_iter()
+        .map(|(part, text)| {
+            let start = Instant::now();
+            let input = match parsed {
+                Some((ref prev, ref input)) if *prev == text => Ok(input),
+                _ => match day.parse(&text) {
+                    Ok(input) => Ok(&parsed.insert((text, input)).1),
+                    Err(e) => Err(format!("day {}: {e}", day.day())),
+                },
+            };
+            let answer = input.and_then(|input| {
+                day.solve(input.as_ref(), part)
+                    .map_err(|e| format!("day {} part {part}: {e}", day.day()))
+            });
+            Outcome { day: day.day(), part, answer, time: start.elapsed() }
+        })
+        .collect()
+}
+
+/// A failure for every requested part of a day whose input couldn't be read.
+fn unreadable(day: &dyn Runner, args: &RunArgs, e: Box<dyn Error>) -> Vec<Outcome> {
+    args.parts()
+        .into_iter()
+        .map(|part| Outcome { day: day.day(), part, answer: Err(e.to_string()), time: Duration::ZERO })
+        .collect()
+}
+
+fn failed(outcomes: &[Outcome]) -> Result<(), Box<dyn Error>> {
+    match outcomes.iter().filter(|o| o.answer.is_err()).count() {
+        0 => Ok(()),
+        n => Err(format!("{n} part(s) failed").into()),
+    }
+}
+
+/// Solves every selected day, carrying on past a day that fails.
+pub fn run(registry: &Registry, inputs: &Inputs, args: &RunArgs) -> Result<(), Box<dyn Error>> {
+    let stdin = read_stdin(args)?;
+
+    let mut outcomes = vec!();
+    for day in selected_days(registry, args)? {
+        match read_inputs(inputs, day, args, &stdin) {
+            Ok(texts) => outcomes.extend(solve_day(day, texts)),
+            Err(e) => outcomes.extend(unreadable(day, args, e)),
+        }
+    }
+
+    output::write_outcomes(&mut io::stdout().lock(), &outcomes, args.format)?;
+    failed(&outcomes)
+}
+
+/// Like [`run`], but every day and part is solved on a thread pool.
+pub fn run_parallel(registry: &Registry, inputs: &Inputs, args: &RunArgs) -> Result<(), Box<dyn Error>> {
+    let stdin = read_stdin(args)?;
+
+    let mut days = vec!();
+    let mut unread = vec!();
+    for day in selected_days(registry, args)? {
+        match read_inputs(inputs, day, args, &stdin) {
+            Ok(texts) => days.push((day, texts)),
+            Err(e) => unread.extend(unreadable(day, args, e)),
+        }
+    }
+
+    let threads = args.threads.map_or_else(parallel::default_threads, |t| t as usize);
+    let mut summary = parallel::solve_all(&days, threads);
+    /* Back in day order, the parts of each day staying in theirs */
+    summary.outcomes.extend(unread);
+    summary.outcomes.sort_by_key(|o| o.day);
+
+    output::write_outcomes(&mut io::stdout().lock(), &summary.outcomes, args.format)?;
+
+    /* Keep stdout parseable for the other formats */
+    let cpu = summary.cpu();
+    let report = format!("{} parts on {} thread(s): {:.2?} wall time, {:.2?} CPU time ({:.1}x)",
+                         summary.outcomes.len(),
+                         summary.threads,
+                         summary.wall,
+                         cpu,
+                         cpu.as_secs_f64() / summary.wall.as_secs_f64().max(f64::EPSILON));
+    match args.format {
+        output::Format::Text => println!("{report}"),
+        _ => eprintln!("{report}"),
+    }
+
+    failed(&summary.outcomes)
+}
+
+pub fn verify(registry: &Registry, inputs: &Inputs, args: &VerifyArgs) -> Result<(), Box<dyn Error>> {
+    let days = match args.day {
+        Some(n) => vec![registry.get(n).ok_or(format!("day {n} has no solution"))?],
+        None => registry.days().collect(),
+    };
+
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    let mut missing = vec!();
+    for day in days {
+        let mut texts = vec!();
+        let mut paths = vec!();
+        let mut readable = true;
+        for part in Part::ALL {
+            let (path, text) = if args.test {
+                (inputs.example_path(day.day(), part), inputs.example(day.day(), part))
+            } else {
+                (inputs.puzzle_path(day.day()), inputs.puzzle(day.day()))
+            };
+            match text {
+                Ok(text) => {
+                    texts.push((part, text));
+                    paths.push(path);
+                }
+                Err(e) => {
+                    println!("Day {} Part {}: NO INPUT {e}", day.day(), part);
+                    readable = false;
+                }
+            }
+        }
+        if !readable {
+            missing.push(format!("day {}: no input", day.day()));
+        }
+
+        for (outcome, path) in solve_day(day, texts).into_iter().zip(paths) {
+            let part = outcome.part;
+            let mut answers = Answers::load(&path)?;
+            let status = match outcome.answer {
+                Ok(answer) => Status::check(answers.get(part), answer),
+                Err(e) => Status::Fail(e),
+            };
+            match status {
+                Status::Pass => passed += 1,
+                Status::Fail(_) => failed += 1,
+                Status::Unknown(answer) => {
+                    unknown += 1;
+                    if args.record {
+                        answers.set(part, answer);
+                        answers.save(&path)?;
+                    }
+                }
+                Status::Skipped => {}
+            }
+            println!("Day {} Part {}: {}", day.day(), part, status);
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {unknown} unknown, {} without input", missing.len());
+    let mut problems = vec!();
+    if failed > 0 {
+        problems.push(format!("{failed} answer(s) changed"));
+    }
+    problems.extend(missing);
+    if !problems.is_empty() {
+        return Err(problems.join(", ").into());
+    }
+    Ok(())
+}
+
+/// What `run` reads, as benchmarks record it.
+fn input_source(args: &RunArgs) -> String {
+    match &args.input {
+        Some(path) if path.as_os_str() == "-" => String::from("stdin"),
+        Some(path) => path.display().to_string(),
+        None if args.test => String::from("example"),
+        None => String::from("puzzle"),
+    }
+}
+
+pub fn bench(registry: &Registry, inputs: &Inputs, args: &RunArgs) -> Result<(), Box<dyn Error>> {
+    let stdin = read_stdin(args)?;
+    let previous = Report::load(&args.bench_file)?;
+
+    let mut report = Report { iterations: args.iterations, measurements: vec!() };
+    for day in selected_days(registry, args)? {
+        let texts = read_inputs(inputs, day, args, &stdin)?;
+        let measurements = bench::bench_day(day, &texts, &input_source(args), args.iterations)
+            .map_err(|e| format!("day {}: {e}", day.day()))?;
+        report.measurements.extend(measurements);
+    }
+
+    let regressions = bench::print_table(&report, previous.as_ref(), args.threshold);
+    if regressions > 0 {
+        println!("\n{regressions} stage(s) more than {}% slower than the previous run", args.threshold);
+    }
+
+    if let Some(previous) = &previous {
+        report.merge(previous);
+    }
+    report.save(&args.bench_file)?;
+    Ok(())
+}
+
+pub fn new_day(inputs: &Inputs, args: &NewDayArgs) -> Result<(), Box<dyn Error>> {
+    for path in scaffold::new_day(&args.root, inputs, args.day)? {
+        println!("wrote {}", path.display());
+    }
+    println!("\nOnce the example is filled in, record its answers with `aoc verify --test --day {} --record`", args.day);
+    Ok(())
+}
+
+pub fn day1(inputs: &Inputs, args: &Day1Args) -> Result<(), Box<dyn Error>> {
+    let text = read_day_input(inputs, 1, Part::Two, &args.input)?;
+    let vocabulary = Vocabulary::find(&args.vocabulary)?;
+
+    if args.diff {
+        for (line, digits, words) in day1::differences(&text, &Vocabulary::digits(), &vocabulary) {
+            println!("line {line}: {}", digits.line);
+            println!("    digits: {digits}");
+            println!("    {}: {words}", args.vocabulary);
+        }
+        println!();
+    } else if args.explain {
+        for (line, trace) in day1::explain(&text, &vocabulary) {
+            println!("line {line}: {}  {trace}", trace.line);
+        }
+        println!();
+    }
+
+    /* Part 1 has its own example */
+    let digits_text = if args.input.test {
+        read_day_input(inputs, 1, Part::One, &args.input)?
+    } else {
+        text.clone()
+    };
+    let part1 = day1::solve(&digits_text, &Vocabulary::digits(), args.no_digits)
+        .map_err(|e| format!("day 1 part 1: {e}"))?;
+    let part2 = day1::solve(&text, &vocabulary, args.no_digits)
+        .map_err(|e| format!("day 1 part 2: {e}"))?;
+    println!("Day 1 Part 1 Calibration (digits): {part1}");
+    println!("Day 1 Part 2 Calibration ({}): {part2}", args.vocabulary);
+    Ok(())
+}
+
+pub fn day2(inputs: &Inputs, args: &Day2Args) -> Result<(), Box<dyn Error>> {
+    let bag = match &args.bag_file {
+        Some(path) => Bag::load(path)?,
+        None => args.bag.clone(),
+    };
+    if let Some(count) = args.simulate {
+        for game in Simulator::new(bag, args.seed).games(count, 6) {
+            println!("{game}");
+        }
+        return Ok(());
+    }
+    if let Some(count) = args.bench_parsers {
+        let games = Simulator::new(bag, args.seed).games(count, 6);
+        let text = games.iter().map(|game| { game.to_string() }).collect::<Vec<_>>().join("\n");
+        let (regex, single_pass) = day2::bench_parsers(&text, args.iterations)?;
+        println!("{count} games, {} bytes, median of {} runs:", text.len(), args.iterations);
+        println!("regex:       {:>12?}", regex.median());
+        println!("single pass: {:>12?} ({:.1}x faster)", single_pass.median(),
+                 regex.median().as_secs_f64() / single_pass.median().as_secs_f64());
+        return Ok(());
+    }
+
+    let text = read_day_input(inputs, 2, Part::One, &args.input)?;
+    let games = Day2::parse(&text).map_err(|e| format!("day 2: {e}"))?;
+
+    println!("Bag: {bag}");
+    if args.rejected {
+        for game in &games {
+            let violations = bag.violations(game);
+            if violations.is_empty() {
+                continue;
+            }
+            println!("{game}");
+            for violation in violations {
+                println!("    {violation}");
+            }
+        }
+        println!();
+    }
+    if args.infer {
+        let show = |estimates: Vec<day2::Estimate>| {
+            estimates.iter().map(|e| { e.to_string() }).collect::<Vec<_>>().join(", ")
+        };
+        for game in &games {
+            println!("Game {}: {}", game.id, show(day2::infer(&game.draws, args.confidence)));
+        }
+        let all = day2::infer(games.iter().flat_map(|game| { &game.draws }), args.confidence);
+        println!("All games: {}", show(all));
+        println!();
+    }
+    if let Some(count) = args.smallest_bag {
+        match day2::smallest_bag(&games, count) {
+            Some(fit) => println!("Smallest bag for {count} game(s): {fit}"),
+            None => println!("Smallest bag for {count} game(s): there are only {} games", games.len()),
+        }
+    }
+    if let Some(budget) = args.budget {
+        match day2::best_bag(&games, budget) {
+            Some(fit) => println!("Best bag of {budget} cubes: {fit}"),
+            None => println!("Best bag of {budget} cubes: no game fits"),
+        }
+    }
+    if args.pareto {
+        for fit in day2::pareto(&games) {
+            println!("Pareto: {fit}");
+        }
+    }
+    if args.smallest_bag.is_some() || args.budget.is_some() || args.pareto {
+        println!();
+    }
+    println!("Day 2 Part 1 Solution: {}", day2::solve_d2p1(&games, &bag));
+    println!("Day 2 Part 2 Solution: {}", day2::solve_d2p2(&games, &bag));
+    Ok(())
+}
+
+pub fn day3(inputs: &Inputs, args: &Day3Args) -> Result<(), Box<dyn Error>> {
+    if args.stream {
+        let totals = match &args.input.input {
+            Some(path) if path.as_os_str() == "-" => day3::stream(io::stdin().lock()),
+            Some(path) => day3::stream(open_file(path)?),
+            None if args.input.test => day3::stream(inputs.example_reader(3, Part::One)?),
+            None => day3::stream(inputs.puzzle_reader(3)?),
+        };
+        let (part1, part2) = totals.map_err(|e| format!("day 3: {e}"))?;
+        println!("Day 3 Part 1 Solution: {part1}");
+        println!("Day 3 Part 2 Solution: {part2}");
+        return Ok(());
+    }
+
+    let text = read_day_input(inputs, 3, Part::One, &args.input)?;
+    let schematic = Day3::parse(&text).map_err(|e| format!("day 3: {e}"))?;
+
+    if let Some(how) = args.render {
+        print!("{}", day3::render(&schematic, &args.gear_rule, how));
+        return Ok(());
+    }
+    if let Some(format) = args.graph {
+        PartGraph::new(&schematic).write(&mut io::stdout().lock(), format)?;
+        return Ok(());
+    }
+    if args.symbols {
+        for (parts, symbols) in schematic.symbol_report() {
+            let counts = symbols.iter()
+                .map(|(c, points)| { format!("{} {c}", points.len()) })
+                .collect::<Vec<_>>();
+            println!("{parts} part(s): {}", counts.join(", "));
+        }
+        println!();
+    }
+    if args.components {
+        let graph = PartGraph::new(&schematic);
+        for component in graph.components() {
+            let values = component.numbers.iter()
+                .map(|n| { graph.numbers[*n].value.to_string() })
+                .collect::<Vec<_>>();
+            let symbols = component.symbols.iter()
+                .map(|s| { graph.symbols[*s].symbol })
+                .collect::<String>();
+            println!("{} part(s) through {symbols}: {}", values.len(), values.join(", "));
+        }
+        if let Some(busiest) = graph.busiest_symbol() {
+            let symbol = graph.symbols[busiest];
+            println!("Busiest symbol: {} at line {}, column {}, touching {} part(s)",
+                     symbol.symbol, symbol.row + 1, symbol.col + 1, graph.degree(busiest));
+        }
+        println!();
+    }
+    println!("Day 3 Part 1 Solution: {}", day3::solve_part1(&schematic));
+    println!("Day 3 Part 2 Gears ({}): {}", args.gear_rule, day3::solve_gears(&schematic, &args.gear_rule));
+    Ok(())
+}
@@ -1,83 +1,377 @@
-use phf::phf_map;
-
-const CONVERSION_MAP: phf::Map<&'static str, &'static str> = phf_map! {
-   "one" => "1",
-   "two" => "2",
-   "three" => "3",
-   "four" => "4",
-   "five" => "5",
-   "six" => "6",
-   "seven" => "7",
-   "eight" => "8",
-   "nine" => "9",
-   "zero" => "0",
-};
-
-pub fn solve_day1() {
-   println!("Day 1 Part 1 Solution: {}", solve_d1p1(read_day1_file()));
-   println!("Day 1 Part 2 Solution: {}", solve_d1p2(read_day1_file()));
-   println!()
-}
-
-fn solve_d1p1(text: String) -> u32 {
-   let mut sum = 0;
-   for line in text.lines() {
-      let mut nums = vec![];
-      line.chars().for_each(|c| {
-         if c.is_numeric() {
-            nums.push(c.to_digit(10).unwrap())
-         }
-      });
-      sum += get_calibration_value(&nums);
+use std::fmt;
+use std::path::{Path, PathBuf};
+use clap::ValueEnum;
+use thiserror::Error;
+use crate::parse::load_table;
+use crate::solution::{Answer, Solution};
+
+/// The spelled-out digits of part 2. "zero" isn't one of them.
+pub const ENGLISH: [(&str, u32); 9] = [
+   ("one", 1),
+   ("two", 2),
+   ("three", 3),
+   ("four", 4),
+   ("five", 5),
+   ("six", 6),
+   ("seven", 7),
+   ("eight", 8),
+   ("nine", 9),
+];
+
+pub const FRENCH: [(&str, u32); 10] = [
+   ("un", 1),
+   ("une", 1),
+   ("deux", 2),
+   ("trois", 3),
+   ("quatre", 4),
+   ("cinq", 5),
+   ("six", 6),
+   ("sept", 7),
+   ("huit", 8),
+   ("neuf", 9),
+];
+
+pub const GERMAN: [(&str, u32); 10] = [
+   ("eins", 1),
+   ("ein", 1),
+   ("zwei", 2),
+   ("drei", 3),
+   ("vier", 4),
+   ("fünf", 5),
+   ("sechs", 6),
+   ("sieben", 7),
+   ("acht", 8),
+   ("neun", 9),
+];
+
+pub const SPANISH: [(&str, u32); 10] = [
+   ("uno", 1),
+   ("una", 1),
+   ("dos", 2),
+   ("tres", 3),
+   ("cuatro", 4),
+   ("cinco", 5),
+   ("seis", 6),
+   ("siete", 7),
+   ("ocho", 8),
+   ("nueve", 9),
+];
+
+pub const ROMAN: [(&str, u32); 9] = [
+   ("I", 1),
+   ("II", 2),
+   ("III", 3),
+   ("IV", 4),
+   ("V", 5),
+   ("VI", 6),
+   ("VII", 7),
+   ("VIII", 8),
+   ("IX", 9),
+];
+
+/// The vocabularies that can be picked by name.
+pub const VOCABULARIES: [(&str, &[(&str, u32)]); 6] = [
+   ("digits", &[]),
+   ("english", &ENGLISH),
+   ("french", &FRENCH),
+   ("german", &GERMAN),
+   ("spanish", &SPANISH),
+   ("roman", &ROMAN),
+];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day1Error {
+   #[error("line {line}: expected at least one digit")]
+   NoDigits { line: usize },
+   #[error("{word:?} can't stand for {digit}, only for a single digit")]
+   NotADigit { word: String, digit: u32 },
+   #[error("a digit can't be spelled with an empty word")]
+   EmptyWord,
+   #[error("{}: {reason}", .path.display())]
+   Vocabulary { path: PathBuf, reason: String },
+   #[error("line {line}: the calibration values add up to more than {}", Answer::MAX)]
+   Overflow { line: usize },
+}
+
+/// What to do with a line that has no digits in it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MissingDigits {
+   /// Fail with the line number.
+   #[default]
+   Error,
+   /// Leave the line out.
+   Skip,
+   /// Count the line's value as 0.
+   Zero,
+}
+
+/// A digit found in a line, either written as one or spelled out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+   pub digit: u32,
+   /// Byte offset of the token in the line.
+   pub start: usize,
+   pub len: usize,
+}
+
+impl Token {
+   pub fn end(&self) -> usize {
+      self.start + self.len
    }
+}
 
-   sum
+/// The words that count as digits. Digits written as digits always count.
+///
+/// Tokens may overlap and share letters: the first token is the one that starts first and the last
+/// token is the one that starts last, so "twone" reads 2 then 1 and "eightwo" reads 8 then 2. When
+/// two words start at the same place the longer one wins, and a word that lies wholly inside a longer
+/// one isn't read on its own, so "VIII" is 8 from either end rather than 8 then 1.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vocabulary {
+   words: Vec<(String, u32)>,
 }
 
-fn solve_d1p2(text: String) -> u32 {
-   //let text = read_da1_test_file();
-   let mut sum = 0;
-   for line in text.lines() {
-      let new_line = convert_strings_to_num(line);
-      let mut nums = vec![];
-      new_line.chars().for_each(|c| {
-         if c.is_numeric() {
-            nums.push(c.to_digit(10).unwrap());
+impl Vocabulary {
+   pub fn new<W: Into<String>>(words: impl IntoIterator<Item = (W, u32)>) -> Result<Self, Day1Error> {
+      let mut vocabulary = Vocabulary::default();
+      for (word, digit) in words {
+         vocabulary = vocabulary.with_word(word, digit)?;
+      }
+      Ok(vocabulary)
+   }
+
+   /// Only digits, as in part 1.
+   pub fn digits() -> Self {
+      Vocabulary::default()
+   }
+
+   /// Digits and the English words for one to nine, as in part 2.
+   pub fn english() -> Self {
+      Vocabulary::new(ENGLISH).unwrap()
+   }
+
+   /// One of [`VOCABULARIES`].
+   pub fn named(name: &str) -> Option<Self> {
+      VOCABULARIES.iter()
+         .find(|(n, _)| { n.eq_ignore_ascii_case(name) })
+         .map(|(_, words)| { Vocabulary::new(words.iter().copied()).unwrap() })
+   }
+
+   /// A word list mapping each word to its digit, as a TOML table (`un = 1`) or a JSON object
+   /// (`{"un": 1}`) depending on the extension.
+   pub fn load(path: &Path) -> Result<Self, Day1Error> {
+      let error = |reason: String| { Day1Error::Vocabulary { path: path.to_path_buf(), reason } };
+      let words = load_table(path, "word list").map_err(error)?;
+      Vocabulary::new(words).map_err(|e| { error(e.to_string()) })
+   }
+
+   /// A vocabulary by name, or else from a word list file.
+   pub fn find(name_or_path: &str) -> Result<Self, Day1Error> {
+      let path = Path::new(name_or_path);
+      match Vocabulary::named(name_or_path) {
+         Some(vocabulary) => Ok(vocabulary),
+         None if path.exists() => Vocabulary::load(path),
+         None => {
+            let names = VOCABULARIES.map(|(name, _)| { name }).join(", ");
+            let reason = format!("not a word list, or one of {names}");
+            Err(Day1Error::Vocabulary { path: path.to_path_buf(), reason })
          }
-      });
-      sum += get_calibration_value(&nums);
+      }
+   }
+
+   pub fn with_word(mut self, word: impl Into<String>, digit: u32) -> Result<Self, Day1Error> {
+      let word = word.into();
+      if word.is_empty() {
+         return Err(Day1Error::EmptyWord);
+      }
+      if digit > 9 {
+         return Err(Day1Error::NotADigit { word, digit });
+      }
+      self.words.retain(|(w, _)| { *w != word });
+      self.words.push((word, digit));
+      /* Longest first, so the first match at a position is the one that wins */
+      self.words.sort_by(|(a, _), (b, _)| { b.len().cmp(&a.len()).then(a.cmp(b)) });
+      Ok(self)
+   }
+
+   pub fn words(&self) -> impl Iterator<Item = (&str, u32)> {
+      self.words.iter().map(|(w, d)| { (w.as_str(), *d) })
+   }
+
+   /// The longest token that starts at `start`, if any, whether or not it's inside another.
+   pub fn token_at(&self, line: &str, start: usize) -> Option<Token> {
+      let rest = &line[start..];
+      let c = rest.chars().next()?;
+      if let Some(digit) = c.to_digit(10) {
+         return Some(Token { digit, start, len: c.len_utf8() });
+      }
+      self.words.iter()
+         .find(|(word, _)| { rest.starts_with(word.as_str()) })
+         .map(|(word, digit)| { Token { digit: *digit, start, len: word.len() } })
+   }
+
+   /// Whether a token that starts earlier covers all of `token`.
+   fn is_nested(&self, line: &str, token: &Token) -> bool {
+      line[..token.start].char_indices()
+         .filter_map(|(i, _)| { self.token_at(line, i) })
+         .any(|outer| { outer.end() >= token.end() })
+   }
+
+   /// The token at `start`, unless it's part of a longer one.
+   fn read_at(&self, line: &str, start: usize) -> Option<Token> {
+      self.token_at(line, start).filter(|token| { !self.is_nested(line, token) })
+   }
+
+   pub fn first(&self, line: &str) -> Option<Token> {
+      /* Nothing starts before the first token, so nothing can contain it */
+      line.char_indices().find_map(|(i, _)| { self.token_at(line, i) })
+   }
+
+   pub fn last(&self, line: &str) -> Option<Token> {
+      line.char_indices().rev().find_map(|(i, _)| { self.read_at(line, i) })
+   }
+
+   /// The first and last tokens of a line, which are the same token when there's only one.
+   pub fn scan(&self, line: &str) -> Option<(Token, Token)> {
+      Some((self.first(line)?, self.last(line)?))
+   }
+
+   /// Every token in the line, overlapping ones included.
+   pub fn tokens<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Token> + 'a {
+      line.char_indices().filter_map(move |(i, _)| { self.read_at(line, i) })
+   }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+   const DAY: u32 = 1;
+   type Input = String;
+   type Error = Day1Error;
+
+   fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+      Ok(String::from(text))
+   }
+
+   fn part1(text: &Self::Input) -> Result<Answer, Self::Error> {
+      solve_d1p1(text)
+   }
+
+   fn part2(text: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+      Ok(Some(solve_d1p2(text)?))
    }
+}
+
+/// The first digit of the line followed by its last.
+pub fn calibration_value(line: &str, vocabulary: &Vocabulary) -> Option<u32> {
+   let (first, last) = vocabulary.scan(line)?;
+   Some(first.digit * 10 + last.digit)
+}
 
-   sum
+/// How a line's calibration value was read: every token in it, of which the first and last are used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace<'a> {
+   pub line: &'a str,
+   pub tokens: Vec<Token>,
 }
 
-fn get_calibration_value(nums: &Vec<u32>) -> u32 {
-   let mut calibration_value = *nums.first().unwrap();
-   calibration_value = calibration_value * 10 + *nums.last().unwrap();
-   calibration_value
+impl<'a> Trace<'a> {
+   pub fn new(line: &'a str, vocabulary: &Vocabulary) -> Self {
+      Trace { line, tokens: vocabulary.tokens(line).collect() }
+   }
+
+   pub fn first(&self) -> Option<&Token> {
+      self.tokens.first()
+   }
+
+   pub fn last(&self) -> Option<&Token> {
+      self.tokens.last()
+   }
+
+   /// The same as [`calibration_value`].
+   pub fn value(&self) -> Option<u32> {
+      Some(self.first()?.digit * 10 + self.last()?.digit)
+   }
 }
 
-fn convert_strings_to_num(line: &str) -> String {
-   let mut new_line = String::from(line);
-   for entry in CONVERSION_MAP.entries() {
-      let (key, value) = entry;
-      let hits: Vec<_> = line.match_indices(key).collect();
-      for hit in hits {
-         let (idx, _) = hit;
-         new_line.replace_range(idx..idx+1, value);
+/// `[two@1=2] one@3=1 3@6 [four@7=4] -> 24`, with the first and last tokens in brackets.
+impl fmt::Display for Trace<'_> {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let Some(value) = self.value() else {
+         return write!(f, "no digits");
+      };
+      let last = self.tokens.len() - 1;
+      for (i, token) in self.tokens.iter().enumerate() {
+         let word = &self.line[token.start..token.end()];
+         let mut text = format!("{word}@{}", token.start);
+         if token.len > 1 || word.parse() != Ok(token.digit) {
+            text += &format!("={}", token.digit);
+         }
+         if i == 0 || i == last {
+            text = format!("[{text}]");
+         }
+         write!(f, "{text} ")?;
       }
+      write!(f, "-> {value}")
    }
-   new_line
 }
 
-fn read_day1_file() -> String {
-   let day1_test = include_str!("../inputs/day1.txt");
-   String::from(day1_test)
+/// Traces every line, numbered from 1.
+pub fn explain<'a>(text: &'a str, vocabulary: &Vocabulary) -> Vec<(usize, Trace<'a>)> {
+   text.lines()
+      .enumerate()
+      .map(|(i, line)| { (i + 1, Trace::new(line, vocabulary)) })
+      .collect()
+}
+
+/// The lines read differently by the two vocabularies, such as by part 1 and part 2.
+pub fn differences<'a>(text: &'a str, a: &Vocabulary, b: &Vocabulary) -> Vec<(usize, Trace<'a>, Trace<'a>)> {
+   explain(text, a)
+      .into_iter()
+      .zip(explain(text, b))
+      .filter(|((_, a), (_, b))| { a.value() != b.value() })
+      .map(|((i, a), (_, b))| { (i, a, b) })
+      .collect()
+}
+
+/// The calibration values that [`solve`] adds up, with their line numbers counted from 1. A line
+/// without digits fails, is left out or counts as 0, as `missing` says.
+pub fn values<'a>(text: &'a str, vocabulary: &'a Vocabulary, missing: MissingDigits) -> impl Iterator<Item = Result<(usize, u32), Day1Error>> + 'a {
+   text.lines()
+      .enumerate()
+      .filter_map(move |(i, line)| {
+         match (calibration_value(line, vocabulary), missing) {
+            (Some(value), _) => Some(Ok((i + 1, value))),
+            (None, MissingDigits::Error) => Some(Err(Day1Error::NoDigits { line: i + 1 })),
+            (None, MissingDigits::Skip) => None,
+            (None, MissingDigits::Zero) => Some(Ok((i + 1, 0))),
+         }
+      })
+}
+
+/// Adds the value of a line to `sum`, failing with the line number rather than wrapping around.
+fn add(sum: Answer, (line, value): (usize, u32)) -> Result<Answer, Day1Error> {
+   sum.checked_add(value.into()).ok_or(Day1Error::Overflow { line })
+}
+
+pub fn solve(text: &str, vocabulary: &Vocabulary, missing: MissingDigits) -> Result<Answer, Day1Error> {
+   values(text, vocabulary, missing).try_fold(0, |sum, value| { add(sum, value?) })
+}
+
+pub fn solve_d1p1(text: &str) -> Result<Answer, Day1Error> {
+   solve(text, &Vocabulary::digits(), MissingDigits::default())
+}
+
+pub fn solve_d1p2(text: &str) -> Result<Answer, Day1Error> {
+   solve(text, &Vocabulary::english(), MissingDigits::default())
 }
 
 #[cfg(test)]
 mod test {
-   use crate::day1::{solve_d1p1, solve_d1p2};
+   use std::fs;
+   use crate::day1::{add, calibration_value, differences, explain, solve, solve_d1p1, solve_d1p2, values, Day1Error, MissingDigits, Token, Trace, Vocabulary};
+   use crate::solution::Answer;
+   use crate::testing::temp_dir;
 
    fn read_day1_part1_test_file() -> String {
      String::from(include_str!("../inputs/day1_part1_test.txt"))
@@ -89,12 +383,123 @@ mod test {
    }
    #[test]
    fn test_part1() {
-      assert_eq!(solve_d1p1(read_day1_part1_test_file()), 142);
+      assert_eq!(solve_d1p1(&read_day1_part1_test_file()), Ok(142));
    }
 
    #[test]
    fn test_part2() {
-      assert_eq!(solve_d1p2(read_day1_part2_test_file()), 281);
+      assert_eq!(solve_d1p2(&read_day1_part2_test_file()), Ok(281));
+   }
+
+   #[test]
+   fn test_no_digits() {
+      assert_eq!(solve_d1p1("1abc2\nabc\n"), Err(Day1Error::NoDigits { line: 2 }));
+      assert_eq!(solve_d1p2("1abc2\nxyz\n"), Err(Day1Error::NoDigits { line: 2 }));
+
+      let digits = Vocabulary::digits();
+      let text = "1abc2\n\nlog: started\n7\n";
+      assert_eq!(solve(text, &digits, MissingDigits::Error), Err(Day1Error::NoDigits { line: 2 }));
+      assert_eq!(solve(text, &digits, MissingDigits::Skip), Ok(89));
+      assert_eq!(solve(text, &digits, MissingDigits::Zero), Ok(89));
+      assert_eq!(solve(text, &Vocabulary::english(), MissingDigits::Skip), Ok(89));
+
+      /* The totals are the same, the lines that count aren't */
+      let lines = |missing| { values(text, &digits, missing).collect::<Result<Vec<_>, _>>() };
+      assert_eq!(lines(MissingDigits::Skip), Ok(vec![(1, 12), (4, 77)]));
+      assert_eq!(lines(MissingDigits::Zero), Ok(vec![(1, 12), (2, 0), (3, 0), (4, 77)]));
+      assert_eq!(lines(MissingDigits::Error), Err(Day1Error::NoDigits { line: 2 }));
+   }
+
+   #[test]
+   fn test_large_input() {
+      let text = "9x9\n".repeat(100_000);
+      assert_eq!(solve_d1p1(&text), Ok(9_900_000));
+      assert_eq!(solve_d1p2(&text.replace('x', "nine")), Ok(9_900_000));
+
+      /* No input is long enough to reach the limit, so start the sum next to it */
+      assert_eq!(add(Answer::MAX - 99, (7, 99)), Ok(Answer::MAX));
+      assert_eq!(add(Answer::MAX - 98, (7, 99)), Err(Day1Error::Overflow { line: 7 }));
+      assert_eq!(add(Answer::MAX, (3, 0)), Ok(Answer::MAX));
+   }
+
+   #[test]
+   fn test_overlaps() {
+      let english = Vocabulary::english();
+      let token = |digit, start, len| { Token { digit, start, len } };
+
+      assert_eq!(english.scan("twone"), Some((token(2, 0, 3), token(1, 2, 3))));
+      assert_eq!(english.scan("eightwo"), Some((token(8, 0, 5), token(2, 4, 3))));
+      assert_eq!(english.scan("xoneightx"), Some((token(1, 1, 3), token(8, 3, 5))));
+      assert_eq!(english.scan("7"), Some((token(7, 0, 1), token(7, 0, 1))));
+      assert_eq!(english.tokens("sevenineight").map(|t| { t.digit }).collect::<Vec<_>>(), vec![7, 9, 8]);
+
+      /* Only written digits count in part 1, and "zero" doesn't count at all */
+      assert_eq!(calibration_value("twone3", &Vocabulary::digits()), Some(33));
+      assert_eq!(calibration_value("zero5zero", &english), Some(55));
+      assert_eq!(calibration_value("zero", &english), None);
+   }
+
+   #[test]
+   fn test_vocabulary() {
+      let vocabulary = Vocabulary::new([("un", 1), ("une", 1), ("deux", 2)]).unwrap();
+      assert_eq!(vocabulary.scan("deuxune"),
+                 Some((Token { digit: 2, start: 0, len: 4 }, Token { digit: 1, start: 4, len: 3 })));
+      assert_eq!(calibration_value("one2", &vocabulary), Some(22));
+
+      let vocabulary = Vocabulary::english().with_word("zero", 0).unwrap();
+      assert_eq!(calibration_value("zero5", &vocabulary), Some(5));
+
+      assert_eq!(Vocabulary::new([("ten", 10)]), Err(Day1Error::NotADigit { word: String::from("ten"), digit: 10 }));
+      assert_eq!(Vocabulary::new([("", 1)]), Err(Day1Error::EmptyWord));
+   }
+
+   #[test]
+   fn test_explain() {
+      let english = Vocabulary::english();
+      let trace = Trace::new("xtwone3four", &english);
+      assert_eq!(trace.value(), calibration_value("xtwone3four", &english));
+      assert_eq!(trace.to_string(), "[two@1=2] one@3=1 3@6 [four@7=4] -> 24");
+      assert_eq!(Trace::new("a7b", &english).to_string(), "[7@1] -> 77");
+      assert_eq!(Trace::new("abc", &english).to_string(), "no digits");
+
+      let text = read_day1_part2_test_file();
+      let traces = explain(&text, &english);
+      assert_eq!(traces.iter().filter_map(|(_, t)| { t.value() }).sum::<u32>(), 281);
+
+      let text = "1abc2\nxtwone3four\nfour5\n";
+      let changed = differences(text, &Vocabulary::digits(), &english)
+         .into_iter()
+         .map(|(line, a, b)| { (line, a.value(), b.value()) })
+         .collect::<Vec<_>>();
+      assert_eq!(changed, vec![(2, Some(33), Some(24)), (3, Some(55), Some(45))]);
+   }
+
+   #[test]
+   fn test_languages() {
+      let value = |line, name| { calibration_value(line, &Vocabulary::named(name).unwrap()) };
+      assert_eq!(value("troisxquatreneuf", "french"), Some(39));
+      assert_eq!(value("zweinsieben", "german"), Some(27));
+      assert_eq!(value("fünfzweins", "german"), Some(51));
+      assert_eq!(value("cero", "spanish"), None);
+      assert_eq!(value("unodosiete", "spanish"), Some(17));
+
+      /* Numerals inside longer numerals don't count on their own */
+      assert_eq!(value("VIII", "roman"), Some(88));
+      assert_eq!(value("aIIbVIIc", "roman"), Some(27));
+      assert_eq!(Vocabulary::named("roman").unwrap().tokens("xIVIIIx").map(|t| { t.digit }).collect::<Vec<_>>(), vec![4, 8]);
+      assert!(Vocabulary::named("klingon").is_none());
+
+      let dir = temp_dir("day1");
+      fs::write(dir.join("words.toml"), "ichi = 1\nni = 2\n").unwrap();
+      fs::write(dir.join("words.json"), r#"{"ichi": 1, "ni": 2}"#).unwrap();
+      fs::write(dir.join("bad.json"), r#"{"juu": 10}"#).unwrap();
+      for file in ["words.toml", "words.json"] {
+         let vocabulary = Vocabulary::find(dir.join(file).to_str().unwrap()).unwrap();
+         assert_eq!(calibration_value("nichi", &vocabulary), Some(21));
+      }
+      let err = Vocabulary::load(&dir.join("bad.json")).unwrap_err();
+      assert!(err.to_string().ends_with("bad.json: \"juu\" can't stand for 10, only for a single digit"), "{err}");
+      fs::remove_dir_all(dir).unwrap();
    }
 
 }
\ No newline at end of file
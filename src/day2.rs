@@ -1,94 +1,561 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use itertools::Itertools;
 use regex::Regex;
+use regex_syntax::is_word_character;
+use thiserror::Error;
+use crate::bench::{time, Timing};
+use crate::parse::{lines, load_table, Line, ParseError};
+use crate::solution::{Answer, Solution};
 
-struct Game {
-    id: u32,
-    valid: bool,
-    power: u32,
+/// One handful of cubes, `3 blue, 4 red`, in the order they were written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Draw {
+    pub cubes: Vec<(String, u32)>,
 }
 
-fn solve_d2p1(text: String) -> u32 {
-    text.lines()
-        .map(|line| { parse_game(line) })
-        .filter(|game| { game.valid })
+/// A game and every draw of it, written as `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub draws: Vec<Draw>,
+}
+
+/// A draw that showed more cubes of a colour than the bag has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub game: u32,
+    /// Which draw of the game, counting from 1.
+    pub draw: usize,
+    pub colour: String,
+    pub shown: u32,
+    pub limit: u32,
+}
+
+/// How many cubes of each colour the bag holds. A colour that isn't listed has none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bag {
+    pub cubes: BTreeMap<String, u32>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day2Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("{}: {reason}", .path.display())]
+    Bag { path: PathBuf, reason: String },
+    #[error("the parsers disagree on line {line}: {text:?}")]
+    ParsersDisagree { line: usize, text: String },
+}
+
+impl Bag {
+    pub fn new<C: Into<String>>(cubes: impl IntoIterator<Item = (C, u32)>) -> Self {
+        Bag { cubes: cubes.into_iter().map(|(c, n)| { (c.into(), n) }).collect() }
+    }
+
+    /// The bag of the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn standard() -> Self {
+        Bag::new([("red", 12), ("green", 13), ("blue", 14)])
+    }
+
+    pub fn get(&self, colour: &str) -> u32 {
+        self.cubes.get(colour).copied().unwrap_or(0)
+    }
+
+    /// A bag from a file mapping each colour to its count, as a TOML table (`red = 12`) or a JSON
+    /// object (`{"red": 12}`) depending on the extension.
+    pub fn load(path: &Path) -> Result<Self, Day2Error> {
+        let cubes = load_table(path, "bag").map_err(|reason| { Day2Error::Bag { path: path.to_path_buf(), reason } })?;
+        Ok(Bag { cubes })
+    }
+
+    /// Whether every draw of the game could have come out of this bag.
+    pub fn allows(&self, game: &Game) -> bool {
+        self.violations(game).is_empty()
+    }
+
+    /// Every colour of every draw that the bag doesn't have enough cubes for.
+    pub fn violations(&self, game: &Game) -> Vec<Violation> {
+        let mut violations = vec!();
+        for (i, draw) in game.draws.iter().enumerate() {
+            for (colour, shown) in draw.totals() {
+                let limit = self.get(colour);
+                if shown > limit {
+                    violations.push(Violation { game: game.id, draw: i + 1, colour: String::from(colour), shown, limit });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Written the way draws are, `12 red, 13 green, 14 blue`.
+impl FromStr for Bag {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let mut bag = Bag::default();
+        let mut at = 0;
+        loop {
+            let (colour, n, end) = scan_cubes(&line, at)?;
+            *bag.cubes.entry(String::from(colour)).or_default() += n;
+            match s.as_bytes().get(end) {
+                None => return Ok(bag),
+                Some(b';') => return Err(line.error(&s[end..], "',' between cubes")),
+                _ => at = end + 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes = self.cubes.iter()
+            .map(|(colour, n)| { format!("{n} {colour}") })
+            .collect::<Vec<_>>();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "game {}, draw {}: {} {}, but the bag only has {}", self.game, self.draw, self.shown, self.colour, self.limit)
+    }
+}
+
+impl Draw {
+    /// How many cubes of each colour were shown, adding up a colour written more than once.
+    pub fn totals(&self) -> BTreeMap<&str, u32> {
+        let mut totals = BTreeMap::new();
+        for (colour, n) in &self.cubes {
+            *totals.entry(colour.as_str()).or_default() += n;
+        }
+        totals
+    }
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes = self.cubes.iter()
+            .map(|(colour, n)| { format!("{n} {colour}") })
+            .collect::<Vec<_>>();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl Game {
+    /// The largest number of cubes of each colour shown in any one draw.
+    pub fn maxima(&self) -> BTreeMap<&str, u32> {
+        let mut maxima = BTreeMap::new();
+        for draw in &self.draws {
+            for (colour, n) in draw.totals() {
+                let max = maxima.entry(colour).or_default();
+                if n > *max {
+                    *max = n;
+                }
+            }
+        }
+        maxima
+    }
+
+    pub fn get(&self, colour: &str) -> u32 {
+        self.maxima().get(colour).copied().unwrap_or(0)
+    }
+
+    /// The product of the fewest cubes of each colour the game needs, counting every colour of the
+    /// bag and of the game. A colour the game never shows makes it 0.
+    pub fn power(&self, bag: &Bag) -> u64 {
+        let maxima = self.maxima();
+        bag.cubes.keys()
+            .filter(|colour| { !maxima.contains_key(colour.as_str()) })
+            .map(|_| { 0 })
+            .chain(maxima.values().map(|n| { u64::from(*n) }))
+            .product()
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let draws = self.draws.iter()
+            .map(|draw| { draw.to_string() })
+            .collect::<Vec<_>>();
+        write!(f, "Game {}: {}", self.id, draws.join("; "))
+    }
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u32 = 2;
+    type Input = Vec<Game>;
+    type Error = Day2Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Ok(lines(text)
+            .map(parse_game)
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn part1(games: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_d2p1(games, &Bag::standard()).into())
+    }
+
+    fn part2(games: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_d2p2(games, &Bag::standard())))
+    }
+}
+
+pub fn solve_d2p1(games: &[Game], bag: &Bag) -> u32 {
+    games.iter()
+        .filter(|game| { bag.allows(game) })
         .map(|game| { game.id })
-        .collect::<Vec<_>>()
-        .iter()
         .sum()
 }
 
-fn solve_d2p2(text: String) -> u32 {
-    text.lines()
-        .map(|line| { parse_game(line).power })
-        .collect::<Vec<_>>()
-        .iter()
+pub fn solve_d2p2(games: &[Game], bag: &Bag) -> u64 {
+    games.iter()
+        .map(|game| { game.power(bag) })
         .sum()
 }
 
-fn parse_game(line: &str) -> Game {
-    let game_regex = Regex::new(r"Game (?<id>[0-9]+)").unwrap();
-    let (game_text, checks) = line.split_once(":").unwrap();
+/// A bag and the games it allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fit {
+    pub bag: Bag,
+    /// The ids of the allowed games, in the order of the log.
+    pub games: Vec<u32>,
+}
 
-    /* Get Game ID */
-    let Some(ids) = game_regex.captures(game_text) else {
-        panic!("Unable to parse game id: {game_text}");
-    };
+impl Fit {
+    pub fn cubes(&self) -> u64 {
+        self.bag.cubes.values().map(|n| { u64::from(*n) }).sum()
+    }
 
-    let id = ids["id"].to_string().parse::<u32>().unwrap();
+    pub fn id_sum(&self) -> u32 {
+        self.games.iter().sum()
+    }
+}
 
-    let red_regex = Regex::new(r"(?<red>[0-9]+) red").unwrap();
-    let green_regex = Regex::new(r"(?<green>[0-9]+) green").unwrap();
-    let blue_regex = Regex::new(r"(?<blue>[0-9]+) blue").unwrap();
+impl fmt::Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} cubes ({}) allow {} game(s) with ids summing to {}",
+               self.cubes(), self.bag, self.games.len(), self.id_sum())
+    }
+}
 
-    let mut red = 0;
-    let mut green = 0;
-    let mut blue = 0;
+/// Every bag that is the smallest one to allow its games, which holds for each colour exactly the most
+/// any of those games needs, as their maxima tell. Any other bag wastes cubes on the same games. Bags that
+/// allow no game are left out.
+///
+/// Each colour only needs trying at the counts some game needs, so this goes through the product of those
+/// over the colours: fine for a few colours, not for dozens.
+pub fn fits(games: &[Game]) -> Vec<Fit> {
+    let maxima = games.iter().map(|game| { game.maxima() }).collect::<Vec<_>>();
+    let mut counts: BTreeMap<&str, BTreeSet<u32>> = BTreeMap::new();
+    for (colour, n) in maxima.iter().flatten() {
+        counts.entry(*colour).or_insert_with(|| { BTreeSet::from([0]) }).insert(*n);
+    }
+    if counts.is_empty() {
+        return vec!();
+    }
+    let needs = |game: usize, colour: &str| { maxima[game].get(colour).copied().unwrap_or(0) };
 
-    red_regex.captures_iter(checks).for_each(|cap| {
-        let cap_num = cap["red"].to_string().parse::<u32>().unwrap();
-        if cap_num > red {
-            red = cap_num;
+    let mut fits = vec!();
+    for bag in counts.values().map(|ns| { ns.iter().copied() }).multi_cartesian_product() {
+        let bag = counts.keys().copied().zip(bag).collect::<Vec<_>>();
+        let allowed = (0..games.len())
+            .filter(|&game| { bag.iter().all(|&(colour, n)| { needs(game, colour) <= n }) })
+            .collect::<Vec<_>>();
+        /* The same games fit in a smaller bag unless every count is needed by one of them */
+        let tight = bag.iter().all(|&(colour, n)| { allowed.iter().any(|&game| { needs(game, colour) == n }) });
+        if allowed.is_empty() || !tight {
+            continue;
         }
-    });
+        fits.push(Fit {
+            bag: Bag::new(bag.into_iter().filter(|(_, n)| { *n > 0 })),
+            games: allowed.into_iter().map(|game| { games[game].id }).collect(),
+        });
+    }
+    fits
+}
 
-    green_regex.captures_iter(checks).for_each(|cap| {
-        let cap_num = cap["green"].to_string().parse::<u32>().unwrap();
-        if cap_num > green {
-            green = cap_num;
+/// The bag with the fewest cubes that allows at least `count` games, preferring the larger sum of ids.
+pub fn smallest_bag(games: &[Game], count: usize) -> Option<Fit> {
+    fits(games).into_iter()
+        .filter(|fit| { fit.games.len() >= count })
+        .min_by_key(|fit| { (fit.cubes(), Reverse(fit.id_sum())) })
+}
+
+/// The bag of at most `budget` cubes whose games have the largest sum of ids, preferring fewer cubes.
+pub fn best_bag(games: &[Game], budget: u64) -> Option<Fit> {
+    fits(games).into_iter()
+        .filter(|fit| { fit.cubes() <= budget })
+        .min_by_key(|fit| { (Reverse(fit.id_sum()), fit.cubes()) })
+}
+
+/// The bags no other bag beats on both fewer cubes and a larger sum of ids, from the fewest cubes up.
+pub fn pareto(games: &[Game]) -> Vec<Fit> {
+    let mut fits = fits(games);
+    fits.sort_by_key(|fit| { (fit.cubes(), Reverse(fit.id_sum())) });
+
+    let mut best = 0;
+    fits.into_iter()
+        .filter(|fit| {
+            let better = fit.id_sum() > best;
+            best = best.max(fit.id_sum());
+            better
+        })
+        .collect()
+}
+
+/// What the draws say about how many cubes of one colour the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Estimate {
+    pub colour: String,
+    /// The most likely count, which is the most ever shown at once.
+    pub likely: u32,
+    /// The range the count lies in at the requested confidence.
+    pub low: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({} to {})", self.likely, self.colour, self.low, self.high)
+    }
+}
+
+/// Estimates the bag behind `draws`, assuming each draw shows each colour independently and
+/// uniformly at random from none to all of its cubes, with colours not mentioned shown 0 times.
+///
+/// Under that model the most likely count of a colour is its largest draw `M`. The bag can't hold
+/// fewer, and out of `m` draws the chance of never seeing more than `M` of `n` cubes is
+/// `((M + 1) / (n + 1))^m`, so the range goes up to the largest `n` for which that chance is still
+/// at least `1 - confidence`.
+pub fn infer<'a>(draws: impl IntoIterator<Item = &'a Draw>, confidence: f64) -> Vec<Estimate> {
+    assert!(confidence > 0.0 && confidence < 1.0, "confidence must be between 0 and 1");
+    let mut maxima = BTreeMap::new();
+    let mut count = 0;
+    for draw in draws {
+        count += 1;
+        for (colour, n) in draw.totals() {
+            let max = maxima.entry(colour).or_default();
+            if n > *max {
+                *max = n;
+            }
         }
-    });
+    }
+
+    maxima.into_iter()
+        .map(|(colour, max)| {
+            let bound = (f64::from(max) + 1.0) / (1.0 - confidence).powf(1.0 / f64::from(count));
+            /* Nudge past rounding so that an exact bound isn't lost */
+            let high = (bound * (1.0 + f64::EPSILON)).floor() as u32 - 1;
+            Estimate { colour: String::from(colour), likely: max, low: max, high: high.max(max) }
+        })
+        .collect()
+}
+
+/// Plays games out of a known bag following the model of [`infer`], for testing it against the truth.
+pub struct Simulator {
+    pub bag: Bag,
+    state: u64,
+}
+
+impl Simulator {
+    pub fn new(bag: Bag, seed: u64) -> Self {
+        Simulator { bag, state: seed }
+    }
+
+    /* SplitMix64, plenty for making up games */
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
 
-    blue_regex.captures_iter(checks).for_each(|cap| {
-        let cap_num = cap["blue"].to_string().parse::<u32>().unwrap();
-        if cap_num > blue {
-            blue = cap_num;
+    /// A uniform number from 0 to `max`.
+    fn up_to(&mut self, max: u32) -> u32 {
+        (self.next() % (u64::from(max) + 1)) as u32
+    }
+
+    /// One draw, with the colours that didn't come out left unwritten. Draws that show nothing at all
+    /// can't be written down, so they're drawn again unless the bag is empty.
+    pub fn draw(&mut self) -> Draw {
+        loop {
+            let cubes = self.bag.cubes.clone()
+                .into_iter()
+                .map(|(colour, n)| { (colour, self.up_to(n)) })
+                .filter(|(_, n)| { *n > 0 })
+                .collect::<Vec<_>>();
+            if !cubes.is_empty() || self.bag.cubes.values().all(|n| { *n == 0 }) {
+                return Draw { cubes };
+            }
         }
-    });
+    }
+
+    pub fn game(&mut self, id: u32, draws: usize) -> Game {
+        Game { id, draws: (0..draws).map(|_| { self.draw() }).collect() }
+    }
 
-    let valid = if red <= 12 && green <= 13 && blue <= 14 {
-        true
-    } else {
-        false
+    /// `count` games numbered from 1, each of between 1 and `max_draws` draws.
+    pub fn games(&mut self, count: u32, max_draws: u32) -> Vec<Game> {
+        (1..=count)
+            .map(|id| {
+                let draws = self.up_to(max_draws.max(1) - 1) as usize + 1;
+                self.game(id, draws)
+            })
+            .collect()
+    }
+}
+
+/// The cubes of one draw, `3 blue, 4 red`, in order.
+fn parse_cubes<'a>(line: &Line<'a>, within: &'a str) -> Result<Vec<(&'a str, u32)>, ParseError> {
+    static CUBES: OnceLock<Regex> = OnceLock::new();
+    let cubes_regex = CUBES.get_or_init(|| { Regex::new(r"^(?<count>[0-9]+) (?<colour>\w+)$").unwrap() });
+
+    within.split(',')
+        .map(|item| {
+            let item = item.trim();
+            let Some(cap) = cubes_regex.captures(item) else {
+                return Err(line.error(item, "\"<count> <colour>\""));
+            };
+            let count = line.parse::<u32>(cap.name("count").unwrap().as_str(), "a cube count")?;
+            Ok((cap.name("colour").unwrap().as_str(), count))
+        })
+        .collect()
+}
+
+/// The first parser, built on regexes and kept to check and benchmark [`parse_game`] against.
+pub fn parse_game_regex(line: Line) -> Result<Game, ParseError> {
+    let game_regex = Regex::new(r"^Game (?<id>[0-9]+)$").unwrap();
+    let (game_text, checks) = line.split_once(line.text, ":", "':' after the game id")?;
+
+    /* Get Game ID */
+    let Some(ids) = game_regex.captures(game_text) else {
+        return Err(line.error(game_text, "\"Game <id>\""));
+    };
+
+    let id = line.parse::<u32>(ids.name("id").unwrap().as_str(), "a game id")?;
+
+    let draws = checks.split(';')
+        .map(|draw| {
+            let cubes = parse_cubes(&line, draw)?
+                .into_iter()
+                .map(|(colour, n)| { (String::from(colour), n) })
+                .collect();
+            Ok(Draw { cubes })
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    Ok(Game { id, draws })
+}
+
+/// Reads `<count> <colour>` from byte `at` of the line up to the next `,`, `;` or the end of the line,
+/// returning the colour, the count and where it stopped.
+fn scan_cubes<'a>(line: &Line<'a>, at: usize) -> Result<(&'a str, u32, usize), ParseError> {
+    let text = line.text;
+    let fail = || {
+        /* Only now look for where the item ends, to point at all of it */
+        let end = text[at..].find([',', ';']).map_or(text.len(), |i| { at + i });
+        line.error(text[at..end].trim(), "\"<count> <colour>\"")
     };
 
-    let power = red * green * blue;
+    let mut i = text.len() - text[at..].trim_start().len();
+    let digits = i;
+    while text.as_bytes().get(i).is_some_and(|b| { b.is_ascii_digit() }) {
+        i += 1;
+    }
+    let count = &text[digits..i];
+    if count.is_empty() || text.as_bytes().get(i) != Some(&b' ') {
+        return Err(fail());
+    }
+    i += 1;
+
+    let colour_start = i;
+    /* A colour is the same run of letters as the regex's `\w+` */
+    i += text[i..].find(|c| { !is_word_character(c) }).unwrap_or(text.len() - i);
+    let colour = &text[colour_start..i];
+    i = text.len() - text[i..].trim_start().len();
+    if colour.is_empty() || !matches!(text.as_bytes().get(i), None | Some(b',') | Some(b';')) {
+        return Err(fail());
+    }
+
+    Ok((colour, line.parse(count, "a cube count")?, i))
+}
+
+/// Reads a game in a single pass over its line, compiling and allocating nothing, and hands every
+/// cube count to `cubes` with the index of its draw. Returns the game id. Errors are the same as
+/// [`parse_game_regex`]'s.
+pub fn scan_game<'a>(line: Line<'a>, mut cubes: impl FnMut(usize, &'a str, u32)) -> Result<u32, ParseError> {
+    let (header, _) = line.split_once(line.text, ":", "':' after the game id")?;
+    let id = match header.strip_prefix("Game ") {
+        Some(id) if !id.is_empty() && id.bytes().all(|b| { b.is_ascii_digit() }) => line.parse(id, "a game id")?,
+        _ => return Err(line.error(header, "\"Game <id>\"")),
+    };
+
+    let mut at = header.len() + 1;
+    let mut draw = 0;
+    loop {
+        let (colour, n, end) = scan_cubes(&line, at)?;
+        cubes(draw, colour, n);
+        match line.text.as_bytes().get(end) {
+            None => return Ok(id),
+            Some(b';') => draw += 1,
+            _ => {}
+        }
+        at = end + 1;
+    }
+}
 
-    Game { id, valid, power }
+pub fn parse_game(line: Line) -> Result<Game, ParseError> {
+    let mut draws: Vec<Draw> = vec!();
+    let id = scan_game(line, |draw, colour, n| {
+        if draw == draws.len() {
+            draws.push(Draw::default());
+        }
+        draws[draw].cubes.push((String::from(colour), n));
+    })?;
+    Ok(Game { id, draws })
 }
 
-pub fn solve_day2() {
-    println!("Day 2 Part 1 Solution: {}", solve_d2p1(read_day2_file()));
-    println!("Day 2 Part 2 Solution: {}", solve_d2p2(read_day2_file()));
-    println!();
+type GameParser = fn(Line) -> Result<Game, ParseError>;
+
+/// The first line of `text` that `a` and `b` parse differently.
+fn disagreement(text: &str, a: GameParser, b: GameParser) -> Option<Day2Error> {
+    lines(text)
+        .find(|line| { a(*line) != b(*line) })
+        .map(|line| { Day2Error::ParsersDisagree { line: line.number, text: String::from(line.text) } })
 }
 
-fn read_day2_file() -> String {
-    let day1_test = include_str!("../inputs/day2.txt");
-    String::from(day1_test)
+/// Times the regex parser and then [`parse_game`] over every line of `text`, checking that they agree.
+pub fn bench_parsers(text: &str, iterations: u32) -> Result<(Timing, Timing), Day2Error> {
+    let parse_all = |parse: GameParser| {
+        lines(text).map(parse).collect::<Result<Vec<_>, _>>()
+    };
+    if let Some(e) = disagreement(text, parse_game_regex, parse_game) {
+        return Err(e);
+    }
+
+    let regex = time(iterations, || { parse_all(parse_game_regex) })?;
+    let single_pass = time(iterations, || { parse_all(parse_game) })?;
+    Ok((regex, single_pass))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day2::{solve_d2p1, solve_d2p2};
+    use std::fs;
+    use crate::day2::{bench_parsers, best_bag, disagreement, infer, pareto, parse_game, parse_game_regex, smallest_bag, solve_d2p1, solve_d2p2, Bag, Day2, Day2Error, Draw, Estimate, Game, Simulator, Violation};
+    use crate::parse::{lines, Line, ParseError};
+    use crate::solution::Solution;
+    use crate::testing::temp_dir;
 
     fn read_day2_test_file() -> String {
         let day1_test_file = include_str!("../inputs/day2_test.txt");
@@ -96,11 +563,156 @@ mod test {
     }
     #[test]
     fn test_part1() {
-        assert_eq!(solve_d2p1(read_day2_test_file()), 8);
+        assert_eq!(solve_d2p1(&Day2::parse(&read_day2_test_file()).unwrap(), &Bag::standard()), 8);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_d2p2(read_day2_test_file()), 2286)
+        assert_eq!(solve_d2p2(&Day2::parse(&read_day2_test_file()).unwrap(), &Bag::standard()), 2286)
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let text = "Game 1: 3 blue, 4 red\nGame two: 1 red\n";
+        assert_eq!(Day2::parse(text).err(), Some(Day2Error::Parse(ParseError {
+            line: 2,
+            column: 1,
+            expected: "\"Game <id>\"",
+            found: String::from("\"Game two\""),
+        })));
+
+        let err = Day2::parse("Game 1: 3 blue, four red").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 17: expected \"<count> <colour>\", found \"four red\"");
+    }
+
+    #[test]
+    fn test_draws() {
+        let text = read_day2_test_file();
+        let games = Day2::parse(&text).unwrap();
+        assert_eq!(games[0].draws.len(), 3);
+        assert_eq!(games[0].draws[1].to_string(), "1 red, 2 green, 6 blue");
+        let written = games.iter().map(|game| { game.to_string() }).collect::<Vec<_>>();
+        assert_eq!(written.join("\n"), text.trim_end());
+
+        let bag = Bag::standard();
+        assert!(bag.violations(&games[0]).is_empty());
+        assert_eq!(bag.violations(&games[2]), vec![
+            Violation { game: 3, draw: 1, colour: String::from("red"), shown: 20, limit: 12 },
+        ]);
+        assert_eq!(bag.violations(&games[3]).iter().map(|v| { v.to_string() }).collect::<Vec<_>>(), vec![
+            "game 4, draw 3: 15 blue, but the bag only has 14",
+            "game 4, draw 3: 14 red, but the bag only has 12",
+        ]);
+    }
+
+    #[test]
+    fn test_infer() {
+        let draws = ["3 blue, 4 red", "1 red, 2 green, 6 blue", "2 green"]
+            .map(|draw| { Draw { cubes: draw.parse::<Bag>().unwrap().cubes.into_iter().collect() } });
+        let estimates = infer(&draws, 0.9);
+        let estimate = |colour: &str, likely, high| {
+            Estimate { colour: String::from(colour), likely, low: likely, high }
+        };
+        /* (M + 1) / 0.1^(1/3) - 1 */
+        assert_eq!(estimates, vec![estimate("blue", 6, 14), estimate("green", 2, 5), estimate("red", 4, 9)]);
+        assert_eq!(infer(&draws[..1], 0.9)[0].to_string(), "3 blue (3 to 39)");
+        let huge = Draw { cubes: vec![(String::from("red"), u32::MAX)] };
+        assert_eq!(infer([&huge], 0.9), vec![estimate("red", u32::MAX, u32::MAX)]);
+
+        /* End to end, the truth is found and stays within the range */
+        let bag = Bag::new([("red", 12), ("green", 13), ("blue", 14), ("purple", 2)]);
+        let mut simulator = Simulator::new(bag.clone(), 2023);
+        let games = simulator.games(100, 6);
+        assert_eq!(games.len(), 100);
+        let text = games.iter().map(|game| { game.to_string() }).collect::<Vec<_>>().join("\n");
+        assert_eq!(Day2::parse(&text).unwrap(), games);
+        assert!(games.iter().all(|game| { (1..=6).contains(&game.draws.len()) }));
+        assert_eq!(solve_d2p1(&games, &bag), 5050);
+
+        let estimates = infer(games.iter().flat_map(|game| { &game.draws }), 0.95);
+        for estimate in estimates {
+            let truth = bag.get(&estimate.colour);
+            assert_eq!(estimate.likely, truth);
+            assert!(estimate.low <= truth && truth <= estimate.high);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parsers_agree() {
+        let mut texts = vec![read_day2_test_file()];
+        let games = Simulator::new(Bag::new([("red", 12), ("dark_blue", 3), ("grün", 4)]), 1).games(50, 6);
+        texts.push(games.iter().map(|game| { game.to_string() }).collect::<Vec<_>>().join("\n"));
+        texts.extend([
+            "Game 1: 3 blue,4 red ;  2 green", "Game 1", "Game: 1 red", "Game 1x: 1 red", "Game 99999999999: 1 red",
+            "Game 1:", "Game 1: 3", "Game 1: 3 ", "Game 1: 3  red", "Game 1: 3 red blue", "Game 1: 3 red,",
+            "Game 1: 3 red;; 1 blue", "Game 1: blue 3", "Game 1: 99999999999 red", "Game 1: 3 red!",
+            "Game 1: 3 cafe\u{301}", "Game 1: 3 \u{661}\u{662}, 1 r\u{200d}d",
+        ].map(String::from));
+
+        for text in texts {
+            for line in lines(&text) {
+                assert_eq!(parse_game(line), parse_game_regex(line), "{:?}", line.text);
+            }
+        }
+
+        let err = parse_game(lines("Game 1: 2 red; 3 blue, 4").next().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 24: expected \"<count> <colour>\", found \"4\"");
+        assert!(bench_parsers(&read_day2_test_file(), 1).is_ok());
+
+        let no_draws = |line: Line| { parse_game(line).map(|game| { Game { draws: vec!(), ..game } }) };
+        let err = disagreement(&read_day2_test_file(), parse_game, no_draws).unwrap();
+        assert_eq!(err, Day2Error::ParsersDisagree { line: 1, text: String::from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green") });
+    }
+
+    #[test]
+    fn test_bag_search() {
+        let games = Day2::parse(&read_day2_test_file()).unwrap();
+
+        let one = smallest_bag(&games, 1).unwrap();
+        assert_eq!((one.bag, one.games), (Bag::new([("red", 1), ("green", 3), ("blue", 4)]), vec![2]));
+        let two = smallest_bag(&games, 2).unwrap();
+        assert_eq!((two.cubes(), two.games), (13, vec![2, 5]));
+        assert_eq!(smallest_bag(&games, 5).unwrap().bag, Bag::new([("red", 20), ("green", 13), ("blue", 15)]));
+        assert_eq!(smallest_bag(&games, 6), None);
+
+        let best = best_bag(&games, 14).unwrap();
+        assert_eq!((best.id_sum(), best.bag), (7, Bag::new([("red", 6), ("green", 3), ("blue", 4)])));
+        assert_eq!(best_bag(&games, 7), None);
+        assert_eq!(best_bag(&games, 39).unwrap().to_string(),
+                   "32 cubes (15 blue, 3 green, 14 red) allow 4 game(s) with ids summing to 12");
+
+        let front = pareto(&games).iter().map(|fit| { (fit.cubes(), fit.id_sum()) }).collect::<Vec<_>>();
+        assert_eq!(front, vec![(8, 2), (11, 5), (13, 7), (15, 8), (32, 12), (48, 15)]);
+    }
+
+    #[test]
+    fn test_bag() {
+        let games = Day2::parse("Game 1: 3 blue, 4 red; 7 purple\nGame 2: 1 red, 2 green; 3 blue\n").unwrap();
+        assert_eq!(games[0].get("purple"), 7);
+
+        /* Colours the bag doesn't have make a game impossible */
+        let standard = Bag::standard();
+        assert!(!standard.allows(&games[0]));
+        assert!(standard.allows(&games[1]));
+        assert_eq!(solve_d2p1(&games, &standard), 2);
+        assert_eq!(games[0].power(&standard), 0);
+        assert_eq!(games[1].power(&standard), 6);
+
+        let bag: Bag = "4 red, 3 blue, 7 purple".parse().unwrap();
+        assert_eq!(bag.to_string(), "3 blue, 7 purple, 4 red");
+        assert!(bag.allows(&games[0]));
+        assert!(!bag.allows(&games[1]));
+        assert_eq!(games[0].power(&bag), 84);
+        /* Game 2 never shows purple */
+        assert_eq!(solve_d2p2(&games, &bag), 84);
+        assert!("12 red, 13".parse::<Bag>().is_err());
+
+        let dir = temp_dir("day2");
+        fs::write(dir.join("bag.toml"), "red = 4\nblue = 3\npurple = 7\n").unwrap();
+        fs::write(dir.join("bag.json"), r#"{"red": 4, "blue": 3, "purple": 7}"#).unwrap();
+        assert_eq!(Bag::load(&dir.join("bag.toml")), Ok(bag.clone()));
+        assert_eq!(Bag::load(&dir.join("bag.json")), Ok(bag));
+        assert!(Bag::load(&dir.join("bag.yaml")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
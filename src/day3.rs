@@ -1,192 +1,801 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+use std::str::FromStr;
+use clap::ValueEnum;
 use itertools::Itertools;
+use serde::Serialize;
+use thiserror::Error;
+use crate::grid::{check_width, Grid};
+use crate::parse::{Line, ParseError};
+use crate::solution::{Answer, Solution};
 
-#[derive(PartialEq, Debug, Eq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-fn solve_part1(text: String) -> u32 {
-    /* Find all the symbols */
-    let symbol_positions = text.lines().enumerate().map(|(y, line)| {
-        line.chars()
-            .enumerate()
-            .map(|(x, c)| {
-                if !c.is_numeric() && c != '.' {
-                    Some(Point { x, y })
-                } else {
-                    None
-                }})
-            .flatten()
-            .collect::<Vec<Point>>()})
-        .flatten()
-        .collect::<Vec<Point>>();
-
-    let num_lines = text.lines().count();
-    /* Start building numbers */
-    text.lines().enumerate().map(|(y, line)| {
-        let mut num: u32 = 0;
-        let mut touches_symbol = false;
-        line.chars().enumerate().map(|(x, c)| {
-            if c.is_numeric() {
-                num = 10 * num + c.to_digit(10).unwrap();
-                /* check symbol touch */
-                if !touches_symbol {
-                    get_positions(x, y, line.len(), num_lines).iter().for_each(|pos| {
-                       if symbol_positions.contains(pos) {
-                           touches_symbol = true;
-                       }
-                    });
+pub use crate::grid::Point;
+
+/// A number written in the schematic. Two numbers are the same only if they're in the same place,
+/// whatever their values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct PartNumber {
+    pub value: u32,
+    pub row: usize,
+    /// The columns of its digits.
+    pub col_span: Range<usize>,
+}
+
+impl PartNumber {
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.col_span.clone().map(|x| { Point { x, y: self.row } })
+    }
+}
+
+/// The engine schematic with every number in it, and which number each digit belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schematic {
+    pub grid: Grid<char>,
+    pub numbers: Vec<PartNumber>,
+    /// Index into `numbers` of the number at each cell.
+    owners: Grid<Option<usize>>,
+}
+
+impl Schematic {
+    pub fn new(grid: Grid<char>) -> Self {
+        let mut numbers = vec!();
+        let mut owners = grid.map(|_| { None });
+        for (y, row) in grid.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let digits = row[x..].iter().take_while(|c| { c.is_ascii_digit() }).count();
+                if digits == 0 {
+                    x += 1;
+                    continue;
                 }
-                if x == line.len() - 1 && touches_symbol {
-                    // We found a number at the end of a line, return it.
-                    num
-                } else {
-                    0 // return 0 as were not done with this number yet.
+                let value = row[x..x + digits].iter().fold(0, |acc, c| { acc * 10 + c.to_digit(10).unwrap() });
+                let number = PartNumber { value, row: y, col_span: x..x + digits };
+                for p in number.points() {
+                    owners[p] = Some(numbers.len());
                 }
-            } else {
-                // We are at a symbol so we can return the number (even if it's zero).
-                let ret = if touches_symbol { num } else { 0 };
-                num = 0;
-                touches_symbol = false;
-                ret
-            }})
-            .sum::<u32>()
-    }).sum()
-}
-
-fn solve_part2(text: String) -> u32 {
-    let gears = text.lines().enumerate().map(|(y, line)| {
-        line.chars()
-            .enumerate()
-            .map(|(x, c)| {
-                if c == '*' {
-                    Some(Point { x, y })
-                } else {
-                    None
-                }})
-            .flatten()
-            .collect::<Vec<Point>>()})
-        .flatten()
-        .collect::<Vec<Point>>();
-
-    let num_lines = text.lines().count();
-    let line_size = text.lines().last().unwrap().len();
-
-    gears.iter()
-        .map(|p| {
-            get_positions(p.x, p.y, line_size, num_lines).iter()
-                .map(|pos| { calculate_number(pos, &text) })
-                .filter(|x| { *x > 0 })
-                .unique()
-                .collect::<Vec<u32>>()
-        })
-        .filter(|x| { x.len() == 2 })
-        .map(|x| { x.into_iter().product::<u32>() })
-        .sum()
+                numbers.push(number);
+                x += digits;
+            }
+        }
+        Schematic { grid, numbers, owners }
+    }
+
+    pub fn number_at(&self, p: Point) -> Option<&PartNumber> {
+        self.owners.get(p).copied().flatten().map(|i| { &self.numbers[i] })
+    }
+
+    /// The numbers around `p`, each once however many of its digits touch it, from the top left.
+    pub fn touching(&self, p: Point) -> Vec<&PartNumber> {
+        self.touching_indices(p)
+            .map(|i| { &self.numbers[i] })
+            .collect()
+    }
+
+    fn touching_indices(&self, p: Point) -> impl Iterator<Item = usize> + '_ {
+        self.grid.neighbours8(p)
+            .filter_map(|pos| { self.owners[pos] })
+            .unique()
+    }
+
+    /// Whether any digit of `number` touches a symbol.
+    pub fn is_part(&self, number: &PartNumber) -> bool {
+        number.points().any(|p| { self.grid.neighbours8(p).any(|pos| { is_symbol(self.grid[pos]) }) })
+    }
+
+    pub fn parts(&self) -> impl Iterator<Item = &PartNumber> + '_ {
+        self.numbers.iter().filter(|number| { self.is_part(number) })
+    }
+
+    /// Every symbol with the numbers around it.
+    pub fn symbols(&self) -> impl Iterator<Item = (Point, char, Vec<&PartNumber>)> + '_ {
+        self.grid.iter()
+            .filter(|(_, c)| { is_symbol(**c) })
+            .map(|(p, c)| { (p, *c, self.touching(p)) })
+    }
+
+    /// Every symbol `rule` applies to, with the numbers around it.
+    pub fn gears(&self, rule: &GearRule) -> Vec<(Point, Vec<&PartNumber>)> {
+        self.symbols()
+            .filter(|(_, c, numbers)| { rule.applies(*c, numbers.len()) })
+            .map(|(p, _, numbers)| { (p, numbers) })
+            .collect()
+    }
+
+    /// The symbols grouped by how many parts each touches, then by symbol.
+    pub fn symbol_report(&self) -> BTreeMap<usize, BTreeMap<char, Vec<Point>>> {
+        let mut report: BTreeMap<usize, BTreeMap<char, Vec<Point>>> = BTreeMap::new();
+        for (p, c, numbers) in self.symbols() {
+            report.entry(numbers.len()).or_default().entry(c).or_default().push(p);
+        }
+        report
+    }
+
+    /// What every cell is, with the gears of `rule` and their numbers standing out from other symbols
+    /// and parts.
+    pub fn marks(&self, rule: &GearRule) -> Grid<Mark> {
+        let mut marks = self.grid.map(|c| {
+            match *c {
+                c if c.is_ascii_digit() => Mark::Loose,
+                c if is_symbol(c) => Mark::Symbol,
+                _ => Mark::Blank,
+            }
+        });
+        for p in self.parts().flat_map(|number| { number.points() }) {
+            marks[p] = Mark::Part;
+        }
+        for (gear, numbers) in self.gears(rule) {
+            marks[gear] = Mark::Gear;
+            for p in numbers.iter().flat_map(|number| { number.points() }) {
+                marks[p] = Mark::GearPart;
+            }
+        }
+        marks
+    }
+}
+
+/// The schematic has to be a rectangle for the neighbour lookups.
+impl FromStr for Schematic {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Schematic::new(s.parse()?))
+    }
 }
 
-fn calculate_number(pos: &Point, text: &String) -> u32 {
-    let line = text.lines().nth(pos.y).unwrap();
+/// How many numbers a symbol has to touch for a [`GearRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+/// What a [`GearRule`] makes of the numbers around a gear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+}
 
-    if !line.chars().nth(pos.x).unwrap().is_numeric() {
-        return 0;
+/// Which symbols are gears and what they're worth, written `<symbol> <count> <product|sum>`: `* 2 product`
+/// is the puzzle's rule, `# 3 product` wants exactly three numbers around a `#`, and `any 2+ sum` adds up
+/// the numbers around any symbol touching at least two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GearRule {
+    /// `None` for any symbol.
+    pub symbol: Option<char>,
+    pub arity: Arity,
+    pub combine: Combine,
+}
+
+impl Default for GearRule {
+    /// A `*` touching exactly two numbers, worth their product.
+    fn default() -> Self {
+        GearRule { symbol: Some('*'), arity: Arity::Exactly(2), combine: Combine::Product }
     }
+}
 
-    let mut left_num = vec!();
-    let mut right_num = vec!();
-    let (before, after) = line.split_at(pos.x);
-    for c in before.chars().rev() {
-        if !c.is_numeric() {
-            break;
+impl GearRule {
+    /// Whether a symbol `c` touching `count` numbers is a gear.
+    pub fn applies(&self, c: char, count: usize) -> bool {
+        self.symbol.is_none_or(|symbol| { symbol == c }) && match self.arity {
+            Arity::Exactly(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
         }
-        left_num.push(c.to_digit(10).unwrap());
     }
 
-    for c in after.chars() {
-        if !c.is_numeric() {
-            break;
+    pub fn value(&self, numbers: &[&PartNumber]) -> u64 {
+        let values = numbers.iter().map(|number| { u64::from(number.value) });
+        match self.combine {
+            Combine::Product => values.product(),
+            Combine::Sum => values.sum(),
         }
-        right_num.push(c.to_digit(10).unwrap());
     }
+}
+
+impl FromStr for GearRule {
+    type Err = ParseError;
 
-    let left_num : Vec<u32> = left_num.iter()
-        .rev()
-        .map(|x| { *x })
-        .collect();
-    let num = vec![left_num, right_num].concat();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = Line { number: 1, text: s };
+        let [symbol, count, combine] = s.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(line.error(s.trim(), "\"<symbol> <count> <product|sum>\""));
+        };
 
-    num.iter().fold(0, |acc, x| acc * 10 + x)
+        let mut chars = symbol.chars();
+        let symbol = match (chars.next(), chars.next()) {
+            _ if symbol == "any" => None,
+            (Some(c), None) if is_symbol(c) => Some(c),
+            _ => return Err(line.error(symbol, "a symbol or \"any\"")),
+        };
+        let arity = match count.strip_suffix('+') {
+            Some(n) => Arity::AtLeast(line.parse(n, "a number of parts")?),
+            None => Arity::Exactly(line.parse(count, "a number of parts")?),
+        };
+        let combine = match combine {
+            "product" => Combine::Product,
+            "sum" => Combine::Sum,
+            _ => return Err(line.error(combine, "\"product\" or \"sum\"")),
+        };
+        Ok(GearRule { symbol, arity, combine })
+    }
 }
 
-fn get_positions(x: usize, y: usize, x_max: usize, y_max: usize) -> Vec<Point> {
-    let mut ret = vec!();
-    /*
-      ----------- First
-      |       --- Second
-      |   ----+-- Third
-      |   |   |
-      v   v   v
-    |   |   |   |
-    |   | * |   |
-    |   |   |   |
-     */
-    if x > 0 {
-        ret.push(Point { x: x - 1, y });
-        if y > 0 {
-            ret.push(Point { x: x - 1, y: y - 1 });
+impl fmt::Display for GearRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.symbol {
+            Some(c) => write!(f, "{c} ")?,
+            None => write!(f, "any ")?,
+        }
+        match self.arity {
+            Arity::Exactly(n) => write!(f, "{n} ")?,
+            Arity::AtLeast(n) => write!(f, "{n}+ ")?,
         }
-        if y < y_max {
-            ret.push(Point { x: x - 1, y: y + 1 });
+        match self.combine {
+            Combine::Product => write!(f, "product"),
+            Combine::Sum => write!(f, "sum"),
         }
     }
+}
+
+/// What a cell of the schematic is, as [`render`] shows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// A `.`.
+    Blank,
+    /// A digit of a number touching a symbol.
+    Part,
+    /// A digit of a number touching no symbol.
+    Loose,
+    Symbol,
+    /// A symbol the gear rule applies to.
+    Gear,
+    /// A digit of a number touching a gear.
+    GearPart,
+}
+
+impl Mark {
+    const SHOWN: [Mark; 5] = [Mark::Part, Mark::Loose, Mark::Symbol, Mark::Gear, Mark::GearPart];
 
-    if x < x_max {
-        ret.push(Point { x: x + 1, y });
-        if y > 0 {
-            ret.push(Point { x: x + 1, y: y - 1 });
+    fn ansi(self) -> &'static str {
+        match self {
+            Mark::Blank => "0",
+            Mark::Part => "32",
+            Mark::Loose => "31",
+            Mark::Symbol => "1;33",
+            Mark::Gear => "1;97;45",
+            Mark::GearPart => "1;36",
         }
-        if y < y_max {
-            ret.push(Point { x: x + 1, y: y + 1 });
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            Mark::Blank => "blank",
+            Mark::Part => "part",
+            Mark::Loose => "loose",
+            Mark::Symbol => "symbol",
+            Mark::Gear => "gear",
+            Mark::GearPart => "gear-part",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Mark::Blank => "blank",
+            Mark::Part => "part",
+            Mark::Loose => "not a part",
+            Mark::Symbol => "symbol",
+            Mark::Gear => "gear",
+            Mark::GearPart => "gear part",
         }
     }
+}
+
+/// How [`render`] draws the schematic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Render {
+    /// Coloured with ANSI escapes, for a terminal.
+    Ansi,
+    /// A standalone HTML page.
+    Html,
+}
 
-    if y > 0 {
-        ret.push(Point { x, y: y - 1 })
+const HTML_HEAD: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Day 3 engine schematic</title>
+<style>
+.part { color: #2aa198; }
+.loose { color: #dc322f; }
+.symbol { color: #b58900; font-weight: bold; }
+.gear { color: white; background: #d33682; font-weight: bold; }
+.gear-part { color: #268bd2; font-weight: bold; }
+</style>
+</head>
+<body>
+<pre>
+";
+
+const HTML_TAIL: &str = "</pre>
+</body>
+</html>
+";
+
+fn styled(text: &str, mark: Mark, how: Render) -> String {
+    match (how, mark) {
+        (Render::Ansi, Mark::Blank) => String::from(text),
+        (Render::Ansi, _) => format!("\x1b[{}m{text}\x1b[0m", mark.ansi()),
+        (Render::Html, _) => {
+            let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            match mark {
+                Mark::Blank => text,
+                _ => format!("<span class=\"{}\">{text}</span>", mark.class()),
+            }
+        }
     }
+}
 
-    if y < y_max {
-        ret.push(Point { x, y: y + 1 })
+/// Draws the schematic with every cell styled by its [`Mark`], followed by a legend.
+pub fn render(schematic: &Schematic, rule: &GearRule, how: Render) -> String {
+    let marks = schematic.marks(rule);
+    let mut out = String::new();
+    if how == Render::Html {
+        out.push_str(HTML_HEAD);
+    }
+    for (y, row) in schematic.grid.rows().enumerate() {
+        /* One escape per run of cells marked the same */
+        let runs = row.iter().enumerate().group_by(|(x, _)| { marks[Point { x: *x, y }] });
+        for (mark, run) in &runs {
+            out.push_str(&styled(&run.map(|(_, c)| { *c }).collect::<String>(), mark, how));
+        }
+        out.push('\n');
     }
 
-    ret
+    let legend = Mark::SHOWN.iter()
+        .map(|mark| { styled(mark.label(), *mark, how) })
+        .collect::<Vec<_>>();
+    out.push('\n');
+    out.push_str(&legend.join("  "));
+    out.push('\n');
+    if how == Render::Html {
+        out.push_str(HTML_TAIL);
+    }
+    out
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day3Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("unable to read the schematic: {0}")]
+    Read(String),
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u32 = 3;
+    type Input = Schematic;
+    type Error = Day3Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Ok(text.parse()?)
+    }
+
+    fn part1(schematic: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_part1(schematic))
+    }
+
+    fn part2(schematic: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_part2(schematic)))
+    }
+}
+
+pub fn is_symbol(c: char) -> bool {
+    !c.is_ascii_digit() && c != '.'
+}
+
+pub fn solve_part1(schematic: &Schematic) -> u64 {
+    schematic.parts()
+        .map(|number| { u64::from(number.value) })
+        .sum()
+}
+
+pub fn solve_part2(schematic: &Schematic) -> u64 {
+    schematic.gears(&GearRule::default())
+        .iter()
+        .map(|(_, numbers)| { u64::from(numbers[0].value) * u64::from(numbers[1].value) })
+        .sum()
+}
+
+/// The value of every gear by `rule`, added up.
+pub fn solve_gears(schematic: &Schematic, rule: &GearRule) -> u64 {
+    schematic.gears(rule)
+        .iter()
+        .map(|(_, numbers)| { rule.value(numbers) })
+        .sum()
 }
 
-pub fn solve_day3() {
-    println!("Day 3 Part 1 Solution: {}", solve_part1(read_day3_file()));
-    println!("Day 3 Part 2 Solution: {}", solve_part2(read_day3_file()));
-    println!();
+/// A symbol of the schematic, as a node of a [`PartGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SymbolNode {
+    pub symbol: char,
+    pub row: usize,
+    pub col: usize,
 }
 
-fn read_day3_file() -> String {
-    String::from(include_str!("../inputs/day3.txt"))
+/// Parts joined by the symbols they share, with indices into a [`PartGraph`]'s nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub numbers: Vec<usize>,
+    pub symbols: Vec<usize>,
+}
+
+/// The schematic as a bipartite graph: every number and every symbol is a node, and an edge joins a
+/// number to each symbol it touches. Numbers that aren't parts and symbols touching nothing are left
+/// without edges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartGraph {
+    pub numbers: Vec<PartNumber>,
+    pub symbols: Vec<SymbolNode>,
+    /// Pairs of an index into `numbers` and one into `symbols`.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// How [`PartGraph::write`] writes the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// GraphViz, with numbers as boxes and symbols as circles.
+    Dot,
+    /// An object of `numbers`, `symbols` and `edges` as `[number, symbol]` index pairs.
+    Json,
+}
+
+impl PartGraph {
+    pub fn new(schematic: &Schematic) -> Self {
+        let mut symbols = vec!();
+        let mut edges = vec!();
+        for (p, c) in schematic.grid.iter().filter(|(_, c)| { is_symbol(**c) }) {
+            edges.extend(schematic.touching_indices(p).map(|number| { (number, symbols.len()) }));
+            symbols.push(SymbolNode { symbol: *c, row: p.y, col: p.x });
+        }
+        edges.sort();
+        PartGraph { numbers: schematic.numbers.clone(), symbols, edges }
+    }
+
+    pub fn degree(&self, symbol: usize) -> usize {
+        self.edges.iter().filter(|(_, s)| { *s == symbol }).count()
+    }
+
+    /// The symbol touching the most numbers, the first one on a tie.
+    pub fn busiest_symbol(&self) -> Option<usize> {
+        let mut degrees = vec![0; self.symbols.len()];
+        for (_, symbol) in &self.edges {
+            degrees[*symbol] += 1;
+        }
+        (0..self.symbols.len()).max_by_key(|s| { (degrees[*s], Reverse(*s)) })
+    }
+
+    /// The groups of parts that reach each other through shared symbols, in the order of their first
+    /// number.
+    pub fn components(&self) -> Vec<Component> {
+        /* Union-find over the numbers followed by the symbols */
+        let mut parents = (0..self.numbers.len() + self.symbols.len()).collect::<Vec<_>>();
+        fn root(parents: &mut [usize], mut node: usize) -> usize {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        }
+        for (number, symbol) in &self.edges {
+            let (a, b) = (root(&mut parents, *number), root(&mut parents, self.numbers.len() + symbol));
+            parents[a.max(b)] = a.min(b);
+        }
+
+        let mut components: BTreeMap<usize, Component> = BTreeMap::new();
+        for (number, symbol) in &self.edges {
+            let component = components.entry(root(&mut parents, *number))
+                .or_insert_with(|| { Component { numbers: vec!(), symbols: vec!() } });
+            component.numbers.push(*number);
+            component.symbols.push(*symbol);
+        }
+        components.into_values()
+            .map(|mut component| {
+                component.numbers = component.numbers.into_iter().sorted().dedup().collect();
+                component.symbols = component.symbols.into_iter().sorted().dedup().collect();
+                component
+            })
+            .collect()
+    }
+
+    pub fn write(&self, out: &mut impl Write, format: GraphFormat) -> io::Result<()> {
+        match format {
+            GraphFormat::Dot => {
+                writeln!(out, "graph schematic {{")?;
+                writeln!(out, "    node [shape=box];")?;
+                for (i, number) in self.numbers.iter().enumerate() {
+                    writeln!(out, "    n{i} [label=\"{}\"];", number.value)?;
+                }
+                for (i, symbol) in self.symbols.iter().enumerate() {
+                    let label = symbol.symbol.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+                    writeln!(out, "    s{i} [label=\"{label}\", shape=circle];")?;
+                }
+                for (number, symbol) in &self.edges {
+                    writeln!(out, "    n{number} -- s{symbol};")?;
+                }
+                writeln!(out, "}}")?;
+            }
+            GraphFormat::Json => {
+                serde_json::to_writer_pretty(&mut *out, self)?;
+                writeln!(out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One row of a [`stream`]ed schematic, with the numbers in it.
+struct Row {
+    cells: Vec<char>,
+    numbers: Vec<(Range<usize>, u32)>,
+}
+
+impl Row {
+    fn new(cells: Vec<char>) -> Self {
+        let mut numbers = vec!();
+        let mut x = 0;
+        while x < cells.len() {
+            let digits = cells[x..].iter().take_while(|c| { c.is_ascii_digit() }).count();
+            if digits == 0 {
+                x += 1;
+                continue;
+            }
+            let value = cells[x..x + digits].iter().fold(0, |acc, c| { acc * 10 + c.to_digit(10).unwrap() });
+            numbers.push((x..x + digits, value));
+            x += digits;
+        }
+        Row { cells, numbers }
+    }
+
+    /// The columns from one left of `span` to one right of it, as far as the row goes.
+    fn around(&self, span: &Range<usize>) -> Range<usize> {
+        span.start.saturating_sub(1)..(span.end + 1).min(self.cells.len())
+    }
+
+    fn has_symbol(&self, span: &Range<usize>) -> bool {
+        self.cells[self.around(span)].iter().any(|c| { is_symbol(*c) })
+    }
+
+    /// The numbers with a digit next to or in column `x`.
+    fn numbers_near(&self, x: usize) -> impl Iterator<Item = u32> + '_ {
+        let columns = x.saturating_sub(1)..x + 2;
+        self.numbers.iter()
+            .filter(move |(span, _)| { span.start < columns.end && columns.start < span.end })
+            .map(|(_, value)| { *value })
+    }
+}
+
+/// Adds the part numbers and gear ratios of the middle row of the window.
+fn solve_window(window: &[Option<Row>; 3], totals: &mut (u64, u64)) {
+    let [above, Some(row), below] = window else {
+        return;
+    };
+    let rows = [above.as_ref(), Some(row), below.as_ref()];
+    let rows = rows.iter().flatten().collect::<Vec<_>>();
+
+    for (span, value) in &row.numbers {
+        if rows.iter().any(|r| { r.has_symbol(span) }) {
+            totals.0 += u64::from(*value);
+        }
+    }
+    for (x, _) in row.cells.iter().enumerate().filter(|(_, c)| { **c == '*' }) {
+        let numbers = rows.iter().flat_map(|r| { r.numbers_near(x) }).collect::<Vec<_>>();
+        if let [a, b] = numbers[..] {
+            totals.1 += u64::from(a) * u64::from(b);
+        }
+    }
+}
+
+/// Solves both parts reading the schematic a row at a time, holding only the rows above and below the
+/// one being solved, so a schematic of millions of rows takes no more memory than one of ten. The
+/// answers are those of [`solve_part1`] and [`solve_part2`].
+pub fn stream(reader: impl BufRead) -> Result<(u64, u64), Day3Error> {
+    let mut width = None;
+    /* Above, being solved and below */
+    let mut window: [Option<Row>; 3] = [None, None, None];
+    let mut totals = (0, 0);
+    for (i, text) in reader.lines().enumerate() {
+        let text = text.map_err(|e| { Day3Error::Read(e.to_string()) })?;
+        let line = Line { number: i + 1, text: &text };
+        check_width(&line, *width.get_or_insert_with(|| { text.chars().count() }))?;
+
+        window.rotate_left(1);
+        window[2] = Some(Row::new(text.chars().collect()));
+        solve_window(&window, &mut totals);
+    }
+    /* The last row has nothing below it */
+    window.rotate_left(1);
+    window[2] = None;
+    solve_window(&window, &mut totals);
+    Ok(totals)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day3::{solve_part1, solve_part2};
+    use itertools::Itertools;
+    use crate::day3::{render, solve_gears, stream, solve_part1, solve_part2, Arity, Combine, Component, Day3, GearRule, GraphFormat, Mark, PartGraph, PartNumber, Point, Render, Schematic};
+    use crate::solution::Solution;
     fn read_day3_test_file() -> String {
         String::from(include_str!("../inputs/day3_test.txt"))
     }
 
     #[test]
     fn test_part1() {
-       assert_eq!(solve_part1(read_day3_test_file()), 4361);
+       assert_eq!(solve_part1(&Day3::parse(&read_day3_test_file()).unwrap()), 4361);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day3_test_file()), 467835);
+        assert_eq!(solve_part2(&Day3::parse(&read_day3_test_file()).unwrap()), 467835);
+    }
+
+    #[test]
+    fn test_edges() {
+        /* Neighbours stop at the last column and row rather than one past them */
+        let schematic = Day3::parse("..12\n..3*\n").unwrap();
+        let corner = schematic.grid.neighbours8(Point { x: 3, y: 1 }).collect::<Vec<_>>();
+        assert_eq!(corner, vec![Point { x: 2, y: 0 }, Point { x: 3, y: 0 }, Point { x: 2, y: 1 }]);
+        assert_eq!(schematic.touching(Point { x: 3, y: 1 }).len(), 2);
+        assert_eq!(schematic.number_at(Point { x: 3, y: 0 }).map(|n| { n.value }), Some(12));
+
+        let grid = Day3::parse("..12\n..3*\n").unwrap();
+        assert_eq!((solve_part1(&grid), solve_part2(&grid)), (15, 36));
+        let grid = Day3::parse("....\n..2*\n..*4\n").unwrap();
+        assert_eq!((solve_part1(&grid), solve_part2(&grid)), (6, 16));
+    }
+
+    #[test]
+    fn test_numbers() {
+        let schematic: Schematic = read_day3_test_file().parse().unwrap();
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.numbers[0], PartNumber { value: 467, row: 0, col_span: 0..3 });
+        assert_eq!(schematic.number_at(Point { x: 7, y: 2 }), Some(&PartNumber { value: 633, row: 2, col_span: 6..9 }));
+        assert_eq!(schematic.parts().count(), 8);
+
+        /* Two different parts with the same value still make a gear */
+        let schematic: Schematic = "35.\n.*.\n.35\n".parse().unwrap();
+        assert_eq!(schematic.gears(&GearRule::default()).len(), 1);
+        assert_eq!(solve_part2(&schematic), 1225);
+
+        /* One number touching a gear three times is still only one part */
+        let schematic: Schematic = "123\n.*.\n".parse().unwrap();
+        assert_eq!(schematic.touching(Point { x: 1, y: 1 }).len(), 1);
+        assert_eq!(solve_part2(&schematic), 0);
+
+        /* Numbers on the last column and the last row */
+        let schematic: Schematic = "...\n.*1\n..2\n".parse().unwrap();
+        assert_eq!(schematic.numbers[1], PartNumber { value: 2, row: 2, col_span: 2..3 });
+        assert_eq!((solve_part1(&schematic), solve_part2(&schematic)), (3, 2));
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_gear_rules() {
+        let schematic: Schematic = read_day3_test_file().parse().unwrap();
+        assert_eq!(solve_gears(&schematic, &GearRule::default()), 467835);
+        assert_eq!(solve_gears(&schematic, &"* 1 product".parse().unwrap()), 617);
+        /* Every symbol touches at least one part in the example */
+        assert_eq!(solve_gears(&schematic, &"any 1+ sum".parse().unwrap()), 4361);
+
+        let rule: GearRule = "# 3+ sum".parse().unwrap();
+        assert_eq!(rule, GearRule { symbol: Some('#'), arity: Arity::AtLeast(3), combine: Combine::Sum });
+        assert_eq!(rule.to_string(), "# 3+ sum");
+        assert_eq!(GearRule::default().to_string(), "* 2 product");
+        let schematic: Schematic = "1.2\n.#.\n3..\n".parse().unwrap();
+        assert_eq!(solve_gears(&schematic, &rule), 6);
+        assert_eq!(solve_gears(&schematic, &"# 3 product".parse().unwrap()), 6);
+        assert_eq!(solve_gears(&schematic, &"* 3 product".parse().unwrap()), 0);
+
+        assert_eq!("1 2 sum".parse::<GearRule>().unwrap_err().to_string(),
+                   "line 1, column 1: expected a symbol or \"any\", found \"1\"");
+        assert_eq!("* two sum".parse::<GearRule>().unwrap_err().column, 3);
+        assert_eq!("* 2 max".parse::<GearRule>().unwrap_err().found, "\"max\"");
+        assert!("* 2".parse::<GearRule>().is_err());
+
+        let report = read_day3_test_file().parse::<Schematic>().unwrap().symbol_report();
+        assert_eq!(report.keys().collect::<Vec<_>>(), vec![&1, &2]);
+        assert_eq!(report[&1][&'*'], vec![Point { x: 3, y: 4 }]);
+        assert_eq!(report[&2][&'*'].len(), 2);
+        assert_eq!(report[&1].values().map(|points| { points.len() }).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_render() {
+        let schematic: Schematic = "12.4\n.*..\n3..&\n".parse().unwrap();
+        let marks = schematic.marks(&GearRule::default());
+        assert_eq!(marks.row(0), &[Mark::GearPart, Mark::GearPart, Mark::Blank, Mark::Loose]);
+        assert_eq!(marks[Point { x: 1, y: 1 }], Mark::Gear);
+        assert_eq!(marks[Point { x: 3, y: 2 }], Mark::Symbol);
+
+        let ansi = render(&schematic, &GearRule::default(), Render::Ansi);
+        assert!(ansi.starts_with("\x1b[1;36m12\x1b[0m.\x1b[31m4\x1b[0m\n"));
+        assert!(ansi.contains("\x1b[1;97;45m*\x1b[0m"));
+
+        let html = render(&schematic, &GearRule::default(), Render::Html);
+        assert!(html.contains("<span class=\"gear-part\">3</span>..<span class=\"symbol\">&amp;</span>\n"));
+        assert!(html.ends_with("<span class=\"gear-part\">gear part</span>\n</pre>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = PartGraph::new(&read_day3_test_file().parse().unwrap());
+        assert_eq!((graph.numbers.len(), graph.symbols.len(), graph.edges.len()), (10, 6, 8));
+        assert_eq!(graph.edges[0], (0, 0));
+        assert_eq!(graph.components().len(), 6);
+        assert_eq!(graph.busiest_symbol(), Some(0));
+        assert_eq!((graph.degree(0), graph.degree(1)), (2, 1));
+
+        /* 2 links both symbols, and with them 1 and 3 */
+        let graph = PartGraph::new(&"1*2*3\n.....\n9.#..\n".parse().unwrap());
+        assert_eq!(graph.components(), vec![Component { numbers: vec![0, 1, 2], symbols: vec![0, 1] }]);
+        assert_eq!(graph.busiest_symbol(), Some(0));
+
+        let mut dot = vec!();
+        graph.write(&mut dot, GraphFormat::Dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.starts_with("graph schematic {\n    node [shape=box];\n    n0 [label=\"1\"];\n"));
+        assert!(dot.contains("    s2 [label=\"#\", shape=circle];\n") && dot.ends_with("    n2 -- s1;\n}\n"));
+
+        let mut json = vec!();
+        graph.write(&mut json, GraphFormat::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["numbers"][3], serde_json::json!({ "value": 9, "row": 2, "col_span": { "start": 0, "end": 1 } }));
+        assert_eq!(json["symbols"][1], serde_json::json!({ "symbol": "*", "row": 0, "col": 3 }));
+        assert_eq!(json["edges"][1], serde_json::json!([1, 0]));
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut texts = vec![read_day3_test_file(), String::new(), String::from("5*5\n"), String::from("..12\n..3*\n")];
+        texts.push(String::from("....\n..2*\n..*4\n"));
+        texts.push(String::from("35.\n.*.\n.35\n"));
+        /* A big schematic made up of digits, dots and a few symbols */
+        let mut state = 7_u64;
+        let cells = (0..80 * 60)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                b"1234567.........*#+."[(state % 20) as usize] as char
+            })
+            .collect::<Vec<_>>();
+        texts.push(cells.chunks(60).map(|row| { row.iter().collect::<String>() }).join("\n"));
+
+        for text in texts {
+            let schematic = Day3::parse(&text).unwrap();
+            let expected = (solve_part1(&schematic), solve_part2(&schematic));
+            assert_eq!(stream(text.as_bytes()).unwrap(), expected, "{text}");
+        }
+
+        /* Enough gears that the total doesn't fit in a u32 */
+        let text = "999*999\n.......\n".repeat(5000);
+        let expected = (5000 * 1998, 5000 * 999 * 999);
+        assert!(expected.1 > u64::from(u32::MAX));
+        assert_eq!((solve_part1(&Day3::parse(&text).unwrap()), solve_part2(&Day3::parse(&text).unwrap())), expected);
+        assert_eq!(stream(text.as_bytes()).unwrap(), expected);
+
+        for ragged in ["467..\n...*.\n..35\n", "12\n345\n"] {
+            assert_eq!(stream(ragged.as_bytes()).unwrap_err(), Day3::parse(ragged).unwrap_err());
+        }
+    }
+
+    #[test]
+    fn test_ragged() {
+        let err = Day3::parse("467..\n...*.\n..35\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 3, column 5: expected a row as wide as the first, found end of line");
+        assert!(Day3::parse(&read_day3_test_file()).is_ok());
+    }
+}
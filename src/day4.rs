@@ -1,28 +1,29 @@
 use std::str::FromStr;
 use itertools::Itertools;
+use thiserror::Error;
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{Answer, Solution};
+
+#[derive(Debug)]
+pub struct Card {
+    pub id: u32,
+    pub points: u32,
+    pub matches: u32,
+}
 
-struct Card {
-    id: u32,
-    points: u32,
-    matches: u32,
+#[derive(Error, Debug, PartialEq)]
+pub enum Day4Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
 }
 
-impl FromStr for Card {
-    type Err = ();
+impl Card {
+    pub fn parse(line: Line) -> Result<Self, ParseError> {
+        let (card_id, nums) = line.split_once(line.text, ":", "':' after the card id")?;
+        let (win_nums, our_nums) = line.split_once(nums, "|", "'|' between the number lists")?;
 
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let mut winning = vec!();
-        let mut numbers = vec!();
-        let (card_id, nums) = line.split_once(":").unwrap();
-        let (win_nums, our_nums) = nums.split_once("|").unwrap();
-
-        for num in win_nums.split_ascii_whitespace() {
-            winning.push(num.parse::<u32>().unwrap())
-        }
-
-        for num in our_nums.split_ascii_whitespace() {
-            numbers.push(num.parse::<u32>().unwrap())
-        }
+        let winning = line.parse_all::<u32>(win_nums, "a winning number")?;
+        let numbers = line.parse_all::<u32>(our_nums, "a number")?;
 
         let mut matches = 0;
         numbers.iter().for_each(|n| {
@@ -37,24 +38,64 @@ impl FromStr for Card {
             0
         };
 
-        let id = card_id.split_whitespace().last().unwrap().parse::<u32>().unwrap() - 1;
+        let id = match card_id.split_whitespace().collect_vec()[..] {
+            ["Card", id] => line.parse::<u32>(id, "a card id")?,
+            _ => return Err(line.error(card_id, "\"Card <id>\"")),
+        };
+        if id == 0 {
+            return Err(line.error(card_id, "a card id from 1"));
+        }
+
+        Ok(Card{ id: id - 1, points, matches })
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
 
-        Ok(Card{ id, points, matches })
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        Card::parse(Line { number: 1, text: line })
     }
 }
 
-fn solve_part1(text: String) -> u32 {
-    text.lines()
-        .map(Card::from_str)
-        .map(|c| { c.unwrap().points })
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u32 = 4;
+    type Input = Vec<Card>;
+    type Error = Day4Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        let mut cards = vec!();
+        for line in lines(text) {
+            let card = Card::parse(line)?;
+            /* Part 2 finds the copies of a card by its id, so the ids have to count up from 1 */
+            if card.id as usize != cards.len() {
+                let (card_id, _) = line.split_once(line.text, ":", "':' after the card id")?;
+                let id = card_id.split_whitespace().last().unwrap_or(card_id);
+                return Err(line.error(id, "cards numbered from 1 in order").into());
+            }
+            cards.push(card);
+        }
+        Ok(cards)
+    }
+
+    fn part1(cards: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_part1(cards).into())
+    }
+
+    fn part2(cards: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_part2(cards).into()))
+    }
+}
+
+pub fn solve_part1(cards: &[Card]) -> u32 {
+    cards.iter()
+        .map(|c| { c.points })
         .sum()
 }
 
-fn solve_part2(text: String) -> u32 {
-    let cards = text.lines()
-        .map(Card::from_str)
-        .map(|c| { c.unwrap() })
-        .collect_vec();
+pub fn solve_part2(cards: &[Card]) -> u32 {
     let mut copies: Vec<u32> = vec![1u32; cards.len()];
     cards.iter()
         .for_each(|c| {
@@ -73,30 +114,35 @@ fn solve_part2(text: String) -> u32 {
     copies.into_iter().sum()
 }
 
-pub fn solve_day4() {
-    println!("Day 4 Part 1 Solution: {}", solve_part1(read_day4_file()));
-    println!("Day 4 Part 2 Solution: {}", solve_part2(read_day4_file()));
-    println!();
-}
-
-fn read_day4_file() -> String {
-    String::from(include_str!("../inputs/day4.txt"))
-}
-
 #[cfg(test)]
 mod test {
-    use crate::day4::{solve_part1, solve_part2};
+    use crate::day4::{solve_part1, solve_part2, Day4};
+    use crate::solution::Solution;
     fn read_day4_test_file() -> String {
        String::from(include_str!("../inputs/day4_test.txt"))
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(read_day4_test_file()), 13)
+        assert_eq!(solve_part1(&Day4::parse(&read_day4_test_file()).unwrap()), 13)
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day4_test_file()), 30)
+        assert_eq!(solve_part2(&Day4::parse(&read_day4_test_file()).unwrap()), 30)
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Day4::parse("Card 1: 41 48 | 83 86\nCard 2: 13 32 20 16 61\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 23: expected '|' between the number lists, found end of line");
+
+        let err = Day4::parse("Card 1: 41 4x | 83 86").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 12: expected a winning number, found \"4x\"");
+
+        let err = Day4::parse("Card 7: 1 | 1").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 6: expected cards numbered from 1 in order, found \"7\"");
+        let err = Day4::parse("Card 1: 1 | 1\nCard  3: 2 | 2").unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 7: expected cards numbered from 1 in order, found \"3\"");
     }
-}
\ No newline at end of file
+}
@@ -1,30 +1,46 @@
-use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::str::FromStr;
-use regex::Regex;
 use itertools::Itertools;
+use thiserror::Error;
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{Answer, Solution};
 
 #[derive(Debug, Clone, Copy)]
-struct Range {
-    val_start: u64,
-    range: u64,
+pub struct Range {
+    pub val_start: u64,
+    pub range: u64,
 }
 
 #[derive(Debug, Clone)]
-struct Garden {
-    seed_to_soil: HashMap<u64, Range>,
-    soil_to_fertilizer: HashMap<u64, Range>,
-    fertilizer_to_water: HashMap<u64, Range>,
-    water_to_light: HashMap<u64, Range>,
-    light_to_temp: HashMap<u64, Range>,
-    temp_to_humid: HashMap<u64, Range>,
-    humid_to_location: HashMap<u64, Range>,
-    seeds: Vec<u64>,
+pub struct Garden {
+    pub seed_to_soil: HashMap<u64, Range>,
+    pub soil_to_fertilizer: HashMap<u64, Range>,
+    pub fertilizer_to_water: HashMap<u64, Range>,
+    pub water_to_light: HashMap<u64, Range>,
+    pub light_to_temp: HashMap<u64, Range>,
+    pub temp_to_humid: HashMap<u64, Range>,
+    pub humid_to_location: HashMap<u64, Range>,
+    pub seeds: Vec<u64>,
 }
 
-fn parse_line(line: &str) -> (u64, Range) {
-    let nums = line.split_whitespace().map(|x| x.parse().unwrap()).collect_vec();
-    (nums[1], Range {val_start: nums[0], range: nums[2]})
+#[derive(Error, Debug, PartialEq)]
+pub enum Day5Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("expected 7 maps from seed to location, found {found}")]
+    MapCount { found: usize },
+    #[error("there are no seeds to plant")]
+    NoSeeds,
+    #[error("seed ranges come in pairs of a start and a length, found {count} numbers")]
+    OddSeeds { count: usize },
+}
+
+fn parse_line(line: Line) -> Result<(u64, Range), ParseError> {
+    let nums = line.parse_all::<u64>(line.text, "a number")?;
+    if nums.len() != 3 {
+        return Err(line.error(line.text, "destination, source and length"));
+    }
+    Ok((nums[1], Range {val_start: nums[0], range: nums[2]}))
 }
 
 fn get_value(num: u64, map: HashMap<u64, Range>) -> u64 {
@@ -37,18 +53,16 @@ fn get_value(num: u64, map: HashMap<u64, Range>) -> u64 {
         }
     }
 
-    let val = match key {
+    match key {
         Some(k) => map.get(&k).unwrap().val_start + (num - k),
         None => num,
-    };
-
-    val
+    }
 }
 
 impl Garden {
-    fn get_locations_for_seeds(&self) -> Vec<u64> {
+    pub fn get_locations_for_seeds(&self) -> Vec<u64> {
        self.seeds.iter()
-           .map(|seed| { *seed })
+           .copied()
            .map(|seed| { get_value(seed, self.seed_to_soil.clone()) })
            .map(|soil| { get_value(soil, self.soil_to_fertilizer.clone())})
            .map(|fertilizer| { get_value(fertilizer, self.fertilizer_to_water.clone()) })
@@ -59,11 +73,14 @@ impl Garden {
            .collect_vec()
     }
 
-    fn get_locations_for_seed_ranges(&self) -> Vec<u64> {
-        self.seeds.iter()
-            .map(|seed| { *seed })
+    pub fn get_locations_for_seed_ranges(&self) -> Result<Vec<u64>, Day5Error> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err(Day5Error::OddSeeds { count: self.seeds.len() });
+        }
+        let locations = self.seeds.iter()
+            .copied()
             .enumerate()
-            .map(|(i, start)| {
+            .flat_map(|(i, start)| {
                if i % 2 == 0 {
                    let mut locations = vec!();
                    let range = self.seeds[i + 1];
@@ -81,44 +98,48 @@ impl Garden {
                    vec!()
                }
             })
-            .flatten()
-            .collect_vec()
+            .collect_vec();
+        Ok(locations)
     }
 }
 
 impl FromStr for Garden {
-    type Err = ();
+    type Err = Day5Error;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        /* Parse seeds */
+        let mut lines = lines(text);
 
-        let (seeds_txt, rest) = text.split_once("\n").unwrap();
-        let (_, seed_nums) = seeds_txt.split_once(" ").unwrap();
-        let seeds = seed_nums.split_whitespace()
-            .map(|x| { x.parse::<u64>().unwrap() })
-            .into_iter()
-            .collect_vec();
+        /* Parse seeds */
+        let Some(seeds_line) = lines.next() else {
+            return Err(Day5Error::NoSeeds);
+        };
+        let (label, seed_nums) = seeds_line.split_once(seeds_line.text, ":", "\"seeds:\"")?;
+        if label != "seeds" {
+            return Err(seeds_line.error(label, "\"seeds:\"").into());
+        }
+        let seeds = seeds_line.parse_all::<u64>(seed_nums, "a seed")?;
 
-        let rest = rest.lines()
-            .filter(|x| { !x.is_empty() })
+        let rest = lines
+            .filter(|x| { !x.text.is_empty() })
             .collect_vec();
 
         let mut maps = vec!();
-        let map_regex = Regex::new(r"^([0-9]+) ([0-9]+) ([0-9]+)").unwrap();
 
         let mut curr_map = HashMap::new();
 
         for x in rest {
-            if map_regex.is_match(x) {
+            if x.text.starts_with(|c: char| c.is_ascii_digit()) {
                 //Numbers
-                let (k, v) = parse_line(x);
+                let (k, v) = parse_line(x)?;
                 curr_map.insert(k, v);
-            } else {
+            } else if x.text.ends_with("map:") {
                 // Another map is starting
                 if !curr_map.is_empty() { // We don't want to include the first empty map
                     maps.push(curr_map);
                     curr_map = HashMap::new();
                 }
+            } else {
+                return Err(x.error(x.text, "a map header or a range").into());
             }
         }
 
@@ -126,9 +147,11 @@ impl FromStr for Garden {
             maps.push(curr_map);
         }
 
-        assert_eq!(maps.len(), 7);
+        if maps.len() != 7 {
+            return Err(Day5Error::MapCount { found: maps.len() });
+        }
         /* Parse seed-to-soil */
-        let seed_to_soil = (*maps.get(0).unwrap()).clone();
+        let seed_to_soil = (*maps.first().unwrap()).clone();
 
         /* Parse soil-to-fertilizer */
         let soil_to_fertilizer = (*maps.get(1).unwrap()).clone();
@@ -161,48 +184,76 @@ impl FromStr for Garden {
     }
 }
 
-fn solve_part1(text: String) -> u64 {
-    Garden::from_str(text.as_str())
-        .unwrap()
-        .get_locations_for_seeds()
-        .into_iter()
-        .min().unwrap()
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u32 = 5;
+    type Input = Garden;
+    type Error = Day5Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Garden::from_str(text)
+    }
+
+    fn part1(garden: &Self::Input) -> Result<Answer, Self::Error> {
+        solve_part1(garden)
+    }
+
+    fn part2(_: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        /* Not proud of my brute force solution */
+        Ok(None)
+    }
 }
 
-fn solve_part2(text: String) -> u64 {
-    Garden::from_str(text.as_str())
-        .unwrap()
-        .get_locations_for_seed_ranges()
+pub fn solve_part1(garden: &Garden) -> Result<u64, Day5Error> {
+    garden.get_locations_for_seeds()
         .into_iter()
         .min()
-        .unwrap()
-}
-
-pub fn solve_day5() {
-    println!("Day 5 Part 1 Solution {}", solve_part1(read_day5_file()));
-    /* Not proud of my brute force solution */
-    // println!("Day 5 Part 2 Solution {}", solve_part2(read_day5_file()));
-    println!();
+        .ok_or(Day5Error::NoSeeds)
 }
 
-fn read_day5_file() -> String {
-    String::from(include_str!("../inputs/day5.txt"))
+pub fn solve_part2(garden: &Garden) -> Result<u64, Day5Error> {
+    garden.get_locations_for_seed_ranges()?
+        .into_iter()
+        .min()
+        .ok_or(Day5Error::NoSeeds)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day5::{solve_part1, solve_part2};
+    use crate::day5::{solve_part1, solve_part2, Day5, Day5Error};
+    use crate::solution::Solution;
 
     fn read_day5_test_file() -> String {
         String::from(include_str!("../inputs/day5_test.txt"))
     }
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(read_day5_test_file()), 35)
+        assert_eq!(solve_part1(&Day5::parse(&read_day5_test_file()).unwrap()), Ok(35))
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day5_test_file()), 46)
+        assert_eq!(solve_part2(&Day5::parse(&read_day5_test_file()).unwrap()), Ok(46))
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let text = read_day5_test_file();
+        let err = Day5::parse(&text.replace("50 98 2", "50 98")).unwrap_err();
+        assert_eq!(err.to_string(), "line 4, column 1: expected destination, source and length, found \"50 98\"");
+
+        let (seeds, _) = text.split_once("\n\n").unwrap();
+        assert_eq!(Day5::parse(seeds).unwrap_err(), Day5Error::MapCount { found: 0 });
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        let text = read_day5_test_file();
+        let odd = Day5::parse(&text.replace("seeds: 79 14 55 13", "seeds: 79 14 55")).unwrap();
+        assert_eq!(solve_part2(&odd), Err(Day5Error::OddSeeds { count: 3 }));
+        let empty = Day5::parse(&text.replace("seeds: 79 14 55 13", "seeds:")).unwrap();
+        assert_eq!(solve_part1(&empty), Err(Day5Error::NoSeeds));
+        assert_eq!(solve_part2(&empty), Err(Day5Error::NoSeeds));
     }
 }
\ No newline at end of file
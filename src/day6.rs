@@ -1,5 +1,7 @@
-use std::str::FromStr;
 use itertools::Itertools;
+use thiserror::Error;
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{Answer, Solution};
 
 struct Strat {
     speed: u64,
@@ -12,13 +14,14 @@ impl Strat {
     }
 }
 
-struct Race {
-    duration: u64,
-    max_distance: u64,
+#[derive(Debug)]
+pub struct Race {
+    pub duration: u64,
+    pub max_distance: u64,
 }
 
 impl Race {
-    fn get_winning_strats(&self) -> Vec<u64> {
+    pub fn get_winning_strats(&self) -> Vec<u64> {
         (0..self.duration).map(|holding| {
                 Strat { speed: holding, time: self.duration - holding }
             })
@@ -28,70 +31,106 @@ impl Race {
     }
 }
 
-fn parse_text_into_races(text: String) -> Vec<Race> {
-    let time_line = text.lines().nth(0).unwrap();
-    let distance_line = text.lines().nth(1).unwrap();
+#[derive(Error, Debug, PartialEq)]
+pub enum Day6Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("expected a line starting with \"{label}:\"")]
+    MissingLine { label: &'static str },
+    #[error("found {times} race times but {distances} distances")]
+    MismatchedRaces { times: usize, distances: usize },
+}
+
+/// The `Time:` and `Distance:` lines of the sheet, with the label removed.
+fn split_sheet(text: &str) -> Result<[(Line<'_>, &str); 2], Day6Error> {
+    let mut lines = lines(text);
+    let mut field = |label: &'static str| {
+        let line = lines.next().ok_or(Day6Error::MissingLine { label })?;
+        let (name, values) = line.split_once(line.text, ":", "':' after the label")?;
+        if name != label {
+            return Err(Day6Error::Parse(line.error(name, label)));
+        }
+        Ok((line, values))
+    };
+    Ok([field("Time")?, field("Distance")?])
+}
 
-    let times = time_line.split_whitespace()
-        .skip(1)
-        .map(|s| { s.parse::<u64>().unwrap() })
-        .collect_vec();
+pub fn parse_text_into_races(text: &str) -> Result<Vec<Race>, Day6Error> {
+    let [(time_line, times), (distance_line, distances)] = split_sheet(text)?;
 
-    let distances = distance_line.split_whitespace()
-        .skip(1)
-        .map(|s| { s.parse::<u64>().unwrap() })
-        .collect_vec();
+    let times = time_line.parse_all::<u64>(times, "a race time")?;
+    let distances = distance_line.parse_all::<u64>(distances, "a record distance")?;
 
-    assert_eq!(times.len(), distances.len());
-    (0..times.len())
+    if times.len() != distances.len() {
+        return Err(Day6Error::MismatchedRaces { times: times.len(), distances: distances.len() });
+    }
+    Ok((0..times.len())
         .map(|i| {
             Race { duration: times[i], max_distance: distances[i] }
         })
-        .collect_vec()
+        .collect_vec())
 }
 
-fn parse_text_into_race(text: String) -> Race {
-    let time_line = text.lines().nth(0).unwrap();
-    let distance_line = text.lines().nth(1).unwrap();
-
-    let time = time_line.split_whitespace()
-        .skip(1)
+/// The kerning is bad, the spaces between the digits aren't really there.
+fn parse_kerned(line: Line, values: &str, expected: &'static str) -> Result<u64, ParseError> {
+    line.parse_all::<u64>(values, expected)?;
+    values.split_whitespace()
         .fold(String::new(), |acc, b| acc + b)
         .parse::<u64>()
-        .unwrap();
+        .map_err(|_| line.error(values.trim(), expected))
+}
 
-    let distance = distance_line.split_whitespace()
-        .skip(1)
-        .fold(String::new(), |acc, b| acc + b)
-        .parse::<u64>()
-        .unwrap();
+pub fn parse_text_into_race(text: &str) -> Result<Race, Day6Error> {
+    let [(time_line, times), (distance_line, distances)] = split_sheet(text)?;
+
+    let time = parse_kerned(time_line, times, "a race time")?;
+    let distance = parse_kerned(distance_line, distances, "a record distance")?;
 
-    Race { duration: time, max_distance: distance }
+    Ok(Race { duration: time, max_distance: distance })
 }
 
-fn solve_part1(text: String) -> u64 {
-    parse_text_into_races(text).iter()
-        .map(|r| { r.get_winning_strats().len() as u64 })
-        .product()
+/// The sheet of paper read both ways: as separate races for part 1 and as one long race for part 2.
+pub struct Sheet {
+    pub races: Vec<Race>,
+    pub race: Race,
 }
 
-fn solve_part2(text: String) -> u64 {
-    parse_text_into_race(text).get_winning_strats().len() as u64
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u32 = 6;
+    type Input = Sheet;
+    type Error = Day6Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Ok(Sheet {
+            races: parse_text_into_races(text)?,
+            race: parse_text_into_race(text)?,
+        })
+    }
+
+    fn part1(sheet: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_part1(&sheet.races))
+    }
+
+    fn part2(sheet: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_part2(&sheet.race)))
+    }
 }
 
-pub fn solve_day6() {
-    println!("Day 6 Part 1 Solution {}", solve_part1(read_day6_file()));
-    println!("Day 6 Part 2 Solution {}", solve_part2(read_day6_file()));
-    println!();
+pub fn solve_part1(races: &[Race]) -> u64 {
+    races.iter()
+        .map(|r| { r.get_winning_strats().len() as u64 })
+        .product()
 }
 
-fn read_day6_file() -> String {
-    String::from(include_str!("../inputs/day6.txt"))
+pub fn solve_part2(race: &Race) -> u64 {
+    race.get_winning_strats().len() as u64
 }
 
 #[cfg(test)]
 mod test {
-    use crate::day6::{solve_part1, solve_part2};
+    use crate::day6::{parse_text_into_race, parse_text_into_races, solve_part1, solve_part2, Day6Error};
 
     fn read_day6_test_file() -> String {
         String::from(include_str!("../inputs/day6_test.txt"))
@@ -99,11 +138,22 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(read_day6_test_file()), 288)
+        assert_eq!(solve_part1(&parse_text_into_races(&read_day6_test_file()).unwrap()), 288)
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day6_test_file()), 71503)
+        assert_eq!(solve_part2(&parse_text_into_race(&read_day6_test_file()).unwrap()), 71503)
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = parse_text_into_races("Time: 7 15\nDistance: 9 40 200").unwrap_err();
+        assert_eq!(err, Day6Error::MismatchedRaces { times: 2, distances: 3 });
+
+        let err = parse_text_into_races("Time: 7 15\nDist: 9 40").unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 1: expected Distance, found \"Dist\"");
+
+        assert_eq!(parse_text_into_race("Time: 7 15").unwrap_err(), Day6Error::MissingLine { label: "Distance" });
     }
 }
\ No newline at end of file
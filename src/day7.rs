@@ -1,9 +1,11 @@
-use std::backtrace::BacktraceStatus::Captured;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 use itertools::Itertools;
 use phf::phf_map;
+use thiserror::Error;
+use crate::parse::{lines, Line, ParseError};
+use crate::solution::{Answer, Solution};
 
 const CARD_VALUE: phf::Map<char, u32> = phf_map!(
     'A' => 14u32,
@@ -37,12 +39,18 @@ const CARD_VALUE_2: phf::Map<char, u32> = phf_map!(
     '2' =>  2u32,
 );
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
-struct Hand {
-    hand: String,
-    strength: u32,
-    wildcard: u32,
-    bid: u32,
+#[derive(Error, Debug, PartialEq)]
+pub enum Day7Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Clone)]
+pub struct Hand {
+    pub hand: String,
+    pub strength: u32,
+    pub wildcard: u32,
+    pub bid: u32,
 }
 
 fn calculate_strength(hand: &str) -> u32 {
@@ -63,7 +71,7 @@ fn calculate_strength(hand: &str) -> u32 {
 
     let mut strength = 0;
 
-    'finder: for k in cards.keys() {
+    for k in cards.keys() {
         match *cards.get(k).unwrap() {
             5 => strength = 6,
             4 => strength = 5,
@@ -98,7 +106,7 @@ fn calculate_wildcard(hand: &str, strength: &u32) -> u32 {
             .sorted()
             .unique()
             .map(|c| {
-                let mut new_hand = hand.replace("J", c.to_string().as_str());
+                let new_hand = hand.replace("J", c.to_string().as_str());
                 calculate_strength(new_hand.as_str())
             })
             .max()
@@ -114,30 +122,46 @@ fn calculate_wildcard(hand: &str, strength: &u32) -> u32 {
     }
 }
 
-impl FromStr for Hand {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (hand, bid) = s.split_once(" ").unwrap();
+impl Hand {
+    pub fn parse(line: Line) -> Result<Self, ParseError> {
+        let (hand, bid) = line.split_once(line.text, " ", "a space before the bid")?;
+        if let Some((i, _)) = hand.char_indices().find(|(_, c)| !CARD_VALUE.contains_key(c)) {
+            return Err(line.error(&hand[i..], "a card from AKQJT98765432"));
+        }
+        if hand.len() != 5 {
+            return Err(line.error(hand, "a hand of five cards"));
+        }
         let hand = String::from(hand);
-        let bid = bid.parse().unwrap();
+        let bid = line.parse(bid, "a bid")?;
         let strength = calculate_strength(hand.as_str());
         let wildcard = calculate_wildcard(hand.as_str(), &strength);
 
         Ok( Hand { hand, strength, bid, wildcard })
     }
 }
-struct CamelCards {
-    hands: Vec<Hand>,
-}
 
-impl FromStr for CamelCards {
-    type Err = ();
+impl FromStr for Hand {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let hands = s.lines()
-            .map(Hand::from_str)
-            .flatten()
+        Hand::parse(Line { number: 1, text: s })
+    }
+}
+
+pub fn parse_hands(s: &str) -> Result<Vec<Hand>, ParseError> {
+    lines(s)
+        .map(Hand::parse)
+        .collect()
+}
+
+/// Hands ranked for part 1, weakest first.
+pub struct CamelCards {
+    pub hands: Vec<Hand>,
+}
+
+impl CamelCards {
+    pub fn new(hands: Vec<Hand>) -> Self {
+        let hands = hands.into_iter()
             .sorted_by(|a, b| {
                 match a.strength.cmp(&b.strength) {
                     Ordering::Equal => {
@@ -156,22 +180,27 @@ impl FromStr for CamelCards {
             })
             .collect_vec();
 
-        Ok(CamelCards { hands })
+        CamelCards { hands }
     }
 }
 
+impl FromStr for CamelCards {
+    type Err = ParseError;
 
-struct CamelCards2 {
-    hands: Vec<Hand>,
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CamelCards::new(parse_hands(s)?))
+    }
 }
 
-impl FromStr for CamelCards2 {
-    type Err = ();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let hands = s.lines()
-            .map(Hand::from_str)
-            .flatten()
+/// Hands ranked for part 2 with jokers wild, weakest first.
+pub struct CamelCards2 {
+    pub hands: Vec<Hand>,
+}
+
+impl CamelCards2 {
+    pub fn new(hands: Vec<Hand>) -> Self {
+        let hands = hands.into_iter()
             .sorted_by(|a, b| {
                 match a.wildcard.cmp(&b.wildcard) {
                     Ordering::Equal => {
@@ -190,37 +219,57 @@ impl FromStr for CamelCards2 {
             })
             .collect_vec();
 
-        Ok(CamelCards2 { hands })
+        CamelCards2 { hands }
+    }
+}
+
+impl FromStr for CamelCards2 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CamelCards2::new(parse_hands(s)?))
     }
 }
 
-fn solve_part1(text: String) -> u32 {
-    CamelCards::from_str(text.as_str()).unwrap().hands.into_iter()
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u32 = 7;
+    type Input = Vec<Hand>;
+    type Error = Day7Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Ok(parse_hands(text)?)
+    }
+
+    fn part1(hands: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_part1(hands).into())
+    }
+
+    fn part2(hands: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_part2(hands).into()))
+    }
+}
+
+pub fn solve_part1(hands: &[Hand]) -> u32 {
+    CamelCards::new(hands.to_vec()).hands.into_iter()
         .enumerate()
         .map(|(i, x)| (i as u32 + 1) * x.bid)
         .sum()
 }
 
-fn solve_part2(text: String) -> u32 {
-    CamelCards2::from_str(text.as_str()).unwrap().hands.into_iter()
+pub fn solve_part2(hands: &[Hand]) -> u32 {
+    CamelCards2::new(hands.to_vec()).hands.into_iter()
         .enumerate()
         .map(|(i, x)| (i as u32 + 1) * x.bid)
         .sum()
 }
 
-pub fn solve_day7() {
-    println!("Day 7 Part 1 Solution: {}", solve_part1(read_day7_file()));
-    println!("Day 7 Part 1 Solution: {}", solve_part2(read_day7_file()));
-    println!();
-}
-
-fn read_day7_file() -> String {
-    String::from(include_str!("../inputs/day7.txt"))
-}
 #[cfg(test)]
 mod test {
-    use itertools::assert_equal;
-    use crate::day7::{solve_part1, solve_part2};
+    use std::str::FromStr;
+    use crate::day7::{solve_part1, solve_part2, CamelCards, Day7};
+    use crate::solution::Solution;
 
     fn read_day7_test_file() -> String {
         String::from(include_str!("../inputs/day7_test.txt"))
@@ -228,11 +277,20 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(read_day7_test_file()), 6440);
+        assert_eq!(solve_part1(&Day7::parse(&read_day7_test_file()).unwrap()), 6440);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day7_test_file()), 5905);
+        assert_eq!(solve_part2(&Day7::parse(&read_day7_test_file()).unwrap()), 5905);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = CamelCards::from_str("32T3K 765\nT55X5 684").err().unwrap();
+        assert_eq!(err.to_string(), "line 2, column 4: expected a card from AKQJT98765432, found \"X5\"");
+
+        let err = Day7::parse("32T3 765").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 1: expected a hand of five cards, found \"32T3\"");
     }
 }
\ No newline at end of file
@@ -3,59 +3,119 @@ use std::str::FromStr;
 use itertools::Itertools;
 use sscanf::sscanf;
 use num::integer::lcm;
+use thiserror::Error;
+use crate::parse::{lines, ParseError};
+use crate::solution::{Answer, Solution};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day8Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error("line {line}: node {node} is never defined")]
+    UndefinedNode { line: usize, node: String },
+    #[error("there is no node {0} in the map")]
+    MissingNode(&'static str),
+    #[error("the map is empty")]
+    Empty,
+}
 
 #[derive(Debug)]
-struct Desert {
-    adj_table: HashMap<String, (String, String)>,
-    path: String,
-    start: Vec<String>,
+pub struct Desert {
+    pub adj_table: HashMap<String, (String, String)>,
+    pub path: String,
+    pub start: Vec<String>,
 }
 
 impl Desert {
-    fn get_next_dir(&self, step: usize) -> char {
+    pub fn get_next_dir(&self, step: usize) -> char {
         self.path.chars().nth(step % self.path.len()).unwrap()
     }
 }
 
 impl FromStr for Desert {
-    type Err = ();
+    type Err = Day8Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (path, rest) = s.split_once("\n").unwrap();
-
-        let adj_table: HashMap<String, (String, String)> = rest.trim()
-            .lines()
-            .map(|c| {
-                let parsed = sscanf!(c, "{String} = ({String}, {String})");
+        let mut lines = lines(s);
+        let Some(path_line) = lines.next() else {
+            return Err(Day8Error::Empty);
+        };
+        let path = path_line.text.trim();
+        if path.is_empty() {
+            return Err(path_line.error(path, "a path of L and R").into());
+        }
+        if let Some((i, _)) = path.char_indices().find(|(_, c)| *c != 'L' && *c != 'R') {
+            return Err(path_line.error(&path[i..], "L or R").into());
+        }
+
+        let nodes = lines
+            .filter(|line| { !line.text.trim().is_empty() })
+            .map(|line| {
+                let parsed = sscanf!(line.text.trim(), "{String} = ({String}, {String})");
                 match parsed {
-                    Ok( (key, left, right) ) => Some((key,(left, right))),
-                    Err(..) => None,
+                    Ok( (key, left, right) ) => Ok((line.number, key, (left, right))),
+                    Err(..) => Err(line.error(line.text, "\"AAA = (BBB, CCC)\"")),
                 }})
-            .flatten()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let adj_table: HashMap<String, (String, String)> = nodes.iter()
+            .map(|(_, key, next)| { (key.clone(), next.clone()) })
             .collect();
 
-        let path = String::from(path.trim());
+        for (line, _, (left, right)) in &nodes {
+            for node in [left, right] {
+                if !adj_table.contains_key(node) {
+                    return Err(Day8Error::UndefinedNode { line: *line, node: node.clone() });
+                }
+            }
+        }
+
+        let path = String::from(path);
 
         let start = adj_table.keys()
-            .map(|k| {
-                if k.ends_with("A") {
+            .filter_map(|k| {
+                if k.ends_with('A') {
                     Some(k.clone())
                 } else {
                     None
                 }
             })
-            .flatten()
             .collect_vec();
         Ok( Desert { adj_table, path, start })
     }
 }
 
-fn solve_part1(text: String) -> usize {
-    let desert = Desert::from_str(text.as_str()).unwrap();
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u32 = 8;
+    type Input = Desert;
+    type Error = Day8Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+        Desert::from_str(text)
+    }
+
+    fn part1(desert: &Self::Input) -> Result<Answer, Self::Error> {
+        Ok(solve_part1(desert)? as Answer)
+    }
+
+    fn part2(desert: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+        Ok(Some(solve_part2(desert) as Answer))
+    }
+}
+
+pub fn solve_part1(desert: &Desert) -> Result<usize, Day8Error> {
+    for node in ["AAA", "ZZZ"] {
+        if !desert.adj_table.contains_key(node) {
+            return Err(Day8Error::MissingNode(node));
+        }
+    }
+
     let mut steps = 0;
     let mut curr_key = String::from("AAA");
 
-    while curr_key != String::from("ZZZ") {
+    while curr_key != "ZZZ" {
         let (left, right) = desert.adj_table.get(curr_key.as_str()).unwrap();
         curr_key = match desert.get_next_dir(steps) {
             'L' => left.clone(),
@@ -65,18 +125,17 @@ fn solve_part1(text: String) -> usize {
         steps += 1;
     }
 
-    steps
+    Ok(steps)
 }
 
-fn is_complete(values: &Vec<(String, usize)>) -> bool {
-    values.into_iter()
+fn is_complete(values: &[(String, usize)]) -> bool {
+    values.iter()
         .fold(true, |acc, (k, _)| {
-            acc & k.ends_with("Z")
+            acc & k.ends_with('Z')
         })
 }
 
-fn solve_part2(test: String) -> usize {
-    let desert = Desert::from_str(test.as_str()).unwrap();
+pub fn solve_part2(desert: &Desert) -> usize {
     let mut steps = desert.start.clone()
         .into_iter()
         .map(|s| {
@@ -87,7 +146,7 @@ fn solve_part2(test: String) -> usize {
     while !is_complete(&steps) {
         steps = steps.into_iter()
             .map(|(k, s)| {
-                if k.ends_with("Z") {
+                if k.ends_with('Z') {
                     (k, s)
                 } else {
                     let (left, right) = desert.adj_table.get(k.as_str()).unwrap();
@@ -104,24 +163,15 @@ fn solve_part2(test: String) -> usize {
 
     /* Get LCM */
     steps.into_iter()
-        .map(|(k, s)| s)
-        .fold(1, |acc, s| lcm(acc, s))
-}
-
-pub fn solve_day8() {
-    println!("Day 8 Part 1 Solution: {}", solve_part1(read_day8_file()));
-    println!("Day 8 Part 2 Solution: {}", solve_part2(read_day8_file()));
-    println!();
-}
-
-fn read_day8_file() -> String {
-    String::from(include_str!("../inputs/day8.txt"))
+        .map(|(_, s)| s)
+        .fold(1, lcm)
 }
 
 
 #[cfg(test)]
 mod test {
-    use crate::day8::{solve_part1, solve_part2};
+    use crate::day8::{solve_part1, solve_part2, Day8, Day8Error};
+    use crate::solution::Solution;
 
     fn read_day8_test_file() -> String {
         String::from(include_str!("../inputs/day8_test.txt"))
@@ -131,18 +181,30 @@ mod test {
         String::from(include_str!("../inputs/day8_test_2.txt"))
     }
 
-    fn read_day8_test_file_3() -> String {
-        String::from(include_str!("../inputs/day8_test_3.txt"))
+    fn read_day8_part2_test_file() -> String {
+        String::from(include_str!("../inputs/day8_part2_test.txt"))
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(read_day8_test_file()), 2);
-        assert_eq!(solve_part1(read_day8_test_file_2()), 6);
+        assert_eq!(solve_part1(&Day8::parse(&read_day8_test_file()).unwrap()), Ok(2));
+        assert_eq!(solve_part1(&Day8::parse(&read_day8_test_file_2()).unwrap()), Ok(6));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(solve_part2(read_day8_test_file_3()), 6)
+        assert_eq!(solve_part2(&Day8::parse(&read_day8_part2_test_file()).unwrap()), 6)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_error() {
+        let err = Day8::parse("LLX\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 3: expected L or R, found \"X\"");
+
+        let err = Day8::parse("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap_err();
+        assert_eq!(err, Day8Error::UndefinedNode { line: 3, node: String::from("BBB") });
+
+        let desert = Day8::parse("LR\n\nAAA = (AAA, AAA)").unwrap();
+        assert_eq!(solve_part1(&desert), Err(Day8Error::MissingNode("ZZZ")));
+    }
+}
@@ -0,0 +1,207 @@
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use crate::parse::{lines, Line, ParseError};
+
+/// A cell of a [`Grid`], counting columns `x` and rows `y` from 0 at the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Steps to the cells that share a side.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Steps to the cells that share a side or a corner, row by row.
+pub const ADJACENT: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+impl Point {
+    /// The point `dx` columns and `dy` rows away, unless that's left of or above the origin.
+    pub fn offset(self, dx: isize, dy: isize) -> Option<Point> {
+        Some(Point { x: self.x.checked_add_signed(dx)?, y: self.y.checked_add_signed(dy)? })
+    }
+}
+
+/// Checks that `line` is `width` characters long, as every row of a grid has to be.
+pub fn check_width(line: &Line, width: usize) -> Result<(), ParseError> {
+    match line.text.char_indices().nth(width) {
+        Some((i, _)) => Err(line.error(&line.text[i..], "the end of the row")),
+        None if line.text.chars().count() < width => Err(line.error_at_end("a row as wide as the first")),
+        None => Ok(()),
+    }
+}
+
+/// A rectangle of cells, stored row by row. Every lookup is bounds checked: [`Grid::get`] and the
+/// neighbour iterators never leave the grid, and indexing outside of it panics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `cells` given row by row.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "a {width} by {height} grid needs {} cells", width * height);
+        Grid { width, height, cells }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Parses one cell from every character of `text`, which has to be a rectangle. `cell` says what
+    /// it expected when a character doesn't make a cell.
+    pub fn parse(text: &str, mut cell: impl FnMut(char) -> Option<T>, expected: &'static str) -> Result<Self, ParseError> {
+        let width = text.lines().next().map_or(0, |l| { l.chars().count() });
+        let mut cells = vec!();
+        let mut height = 0;
+        for line in lines(text) {
+            check_width(&line, width)?;
+            for (i, c) in line.text.char_indices() {
+                cells.push(cell(c).ok_or_else(|| { line.error(&line.text[i..i + c.len_utf8()], expected) })?);
+            }
+            height += 1;
+        }
+        Ok(Grid { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.contains(p).then(|| { &self.cells[p.y * self.width + p.x] })
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        if !self.contains(p) {
+            return None;
+        }
+        Some(&mut self.cells[p.y * self.width + p.x])
+    }
+
+    /// The points `steps` away from `p` that are inside the grid.
+    pub fn steps(&self, p: Point, steps: &'static [(isize, isize)]) -> impl Iterator<Item = Point> + '_ {
+        steps.iter()
+            .filter_map(move |&(dx, dy)| { p.offset(dx, dy) })
+            .filter(|q| { self.contains(*q) })
+    }
+
+    /// The cells above, left, right and below `p`.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.steps(p, &ORTHOGONAL)
+    }
+
+    /// The cells around `p`, diagonals included.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        self.steps(p, &ADJACENT)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {y} is outside a grid of {} rows", self.height);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).map(|y| { self.row(y) })
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(x < self.width, "column {x} is outside a grid of {} columns", self.width);
+        (0..self.height).map(move |y| { &self.cells[y * self.width + x] })
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(|x| { self.column(x) })
+    }
+
+    /// Every point of the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| { (0..width).map(move |x| { Point { x, y } }) })
+    }
+
+    /// Every cell with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.points().zip(&self.cells)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        self.get(p).unwrap_or_else(|| { panic!("{p:?} is outside a {} by {} grid", self.width, self.height) })
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p).unwrap_or_else(|| { panic!("{p:?} is outside a {width} by {height} grid") })
+    }
+}
+
+/// Any rectangle of characters.
+impl FromStr for Grid<char> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse(s, Some, "a character")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grid::{Grid, Point};
+    use crate::parse::ParseError;
+
+    #[test]
+    fn test_parse() {
+        let grid: Grid<char> = "ab1\ncd2\n".parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point { x: 2, y: 1 }], '2');
+        assert_eq!(grid.get(Point { x: 3, y: 0 }), None);
+        assert_eq!(grid.rows().map(|row| { row.iter().collect::<String>() }).collect::<Vec<_>>(), vec!["ab1", "cd2"]);
+        assert_eq!(grid.columns().map(|col| { col.collect::<String>() }).collect::<Vec<_>>(), vec!["ac", "bd", "12"]);
+        assert_eq!(grid.iter().nth(4), Some((Point { x: 1, y: 1 }, &'d')));
+
+        let err = "ab1\ncd\n".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 3: expected a row as wide as the first, found end of line");
+        let err = "ab1\ncd23\n".parse::<Grid<char>>().unwrap_err();
+        assert_eq!(err, ParseError { line: 2, column: 4, expected: "the end of the row", found: String::from("\"3\"") });
+        let err = Grid::parse("12\n3x", |c| { c.to_digit(10) }, "a digit").unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 2: expected a digit, found \"x\"");
+
+        let empty: Grid<char> = "".parse().unwrap();
+        assert_eq!((empty.width(), empty.height(), empty.rows().count()), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let mut grid = Grid::filled(3, 2, 0);
+        let corner = grid.neighbours8(Point { x: 2, y: 1 }).collect::<Vec<_>>();
+        assert_eq!(corner, vec![Point { x: 1, y: 0 }, Point { x: 2, y: 0 }, Point { x: 1, y: 1 }]);
+        assert_eq!(grid.neighbours8(Point { x: 1, y: 0 }).count(), 5);
+        assert_eq!(grid.neighbours4(Point { x: 0, y: 0 }).collect::<Vec<_>>(), vec![Point { x: 1, y: 0 }, Point { x: 0, y: 1 }]);
+        assert_eq!(grid.neighbours4(Point { x: 5, y: 5 }).count(), 0);
+
+        grid[Point { x: 1, y: 1 }] = 7;
+        assert_eq!(grid.map(|n| { n * 2 }).row(1), &[0, 14, 0]);
+        assert_eq!(grid.get_mut(Point { x: 0, y: 2 }), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use crate::solution::Part;
+
+/// Environment variable pointing at the inputs directory, overridden by `--inputs`.
+pub const INPUTS_ENV: &str = "AOC_INPUTS";
+
+pub const DEFAULT_INPUTS: &str = "inputs";
+
+#[derive(Error, Debug)]
+pub enum InputError {
+    #[error("inputs directory {} does not exist, use --inputs or {INPUTS_ENV} to point at it", .0.display())]
+    NoDirectory(PathBuf),
+    #[error("no input for day {day}, expected {}", .path.display())]
+    Missing { day: u32, path: PathBuf },
+    #[error("unable to read {}: {source}", .path.display())]
+    Unreadable { path: PathBuf, source: io::Error },
+}
+
+/// Finds puzzle inputs by convention: `dayN.txt` for the puzzle itself and `dayN_test.txt`
+/// for the example, or `dayN_partP_test.txt` when a part has an example of its own.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    dir: PathBuf,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Inputs { dir: dir.into() }
+    }
+
+    pub fn puzzle_path(&self, day: u32) -> PathBuf {
+        self.dir.join(format!("day{day}.txt"))
+    }
+
+    pub fn example_path(&self, day: u32, part: Part) -> PathBuf {
+        let part_file = self.dir.join(format!("day{day}_part{part}_test.txt"));
+        if part_file.exists() {
+            part_file
+        } else {
+            self.dir.join(format!("day{day}_test.txt"))
+        }
+    }
+
+    pub fn puzzle(&self, day: u32) -> Result<String, InputError> {
+        self.load(day, self.puzzle_path(day))
+    }
+
+    pub fn example(&self, day: u32, part: Part) -> Result<String, InputError> {
+        self.load(day, self.example_path(day, part))
+    }
+
+    /// The puzzle input as a reader, for days that can solve it without holding all of it.
+    pub fn puzzle_reader(&self, day: u32) -> Result<BufReader<File>, InputError> {
+        let path = self.puzzle_path(day);
+        self.check(day, &path)?;
+        open_file(&path)
+    }
+
+    pub fn example_reader(&self, day: u32, part: Part) -> Result<BufReader<File>, InputError> {
+        let path = self.example_path(day, part);
+        self.check(day, &path)?;
+        open_file(&path)
+    }
+
+    fn check(&self, day: u32, path: &Path) -> Result<(), InputError> {
+        if !self.dir.is_dir() {
+            return Err(InputError::NoDirectory(self.dir.clone()));
+        }
+        if !path.exists() {
+            return Err(InputError::Missing { day, path: path.to_path_buf() });
+        }
+        Ok(())
+    }
+
+    fn load(&self, day: u32, path: PathBuf) -> Result<String, InputError> {
+        self.check(day, &path)?;
+        read_file(&path)
+    }
+}
+
+impl Default for Inputs {
+    fn default() -> Self {
+        Inputs::new(DEFAULT_INPUTS)
+    }
+}
+
+/// Reads an input given explicitly, e.g. with `--input`.
+pub fn read_file(path: &Path) -> Result<String, InputError> {
+    fs::read_to_string(path)
+        .map_err(|source| InputError::Unreadable { path: path.to_path_buf(), source })
+}
+
+/// Opens an input given explicitly to be read a line at a time.
+pub fn open_file(path: &Path) -> Result<BufReader<File>, InputError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| InputError::Unreadable { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use crate::input::{InputError, Inputs};
+    use crate::solution::Part;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn test_convention() {
+        let dir = temp_dir("inputs-convention");
+        fs::write(dir.join("day3.txt"), "puzzle").unwrap();
+        fs::write(dir.join("day3_test.txt"), "example").unwrap();
+        fs::write(dir.join("day3_part2_test.txt"), "example 2").unwrap();
+
+        let inputs = Inputs::new(&dir);
+        assert_eq!(inputs.puzzle(3).unwrap(), "puzzle");
+        assert_eq!(inputs.example(3, Part::One).unwrap(), "example");
+        assert_eq!(inputs.example(3, Part::Two).unwrap(), "example 2");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing() {
+        let dir = temp_dir("inputs-missing");
+        let inputs = Inputs::new(&dir);
+        assert!(matches!(inputs.puzzle(4), Err(InputError::Missing { day: 4, .. })));
+
+        let inputs = Inputs::new(dir.join("nope"));
+        assert!(matches!(inputs.puzzle(4), Err(InputError::NoDirectory(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
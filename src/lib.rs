@@ -0,0 +1,41 @@
+//! Advent of Code 2023. Every day implements [`solution::Solution`] and is listed in [`registry`],
+//! which is all the `aoc` binary needs to run, benchmark or verify it.
+
+use crate::solution::Registry;
+
+pub mod answers;
+pub mod bench;
+pub mod cli;
+pub mod commands;
+pub mod grid;
+pub mod input;
+pub mod output;
+pub mod parallel;
+pub mod parse;
+pub mod scaffold;
+pub mod solution;
+#[cfg(test)]
+mod testing;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+
+/// Every day we have a solution for.
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register::<day1::Day1>()
+        .register::<day2::Day2>()
+        .register::<day3::Day3>()
+        .register::<day4::Day4>()
+        .register::<day5::Day5>()
+        .register::<day6::Day6>()
+        .register::<day7::Day7>()
+        .register::<day8::Day8>();
+    registry
+}
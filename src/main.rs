@@ -1,43 +1,32 @@
-use crate::day1::solve_day1;
-use crate::day2::solve_day2;
-use crate::day3::solve_day3;
-use crate::day4::solve_day4;
-use crate::day5::solve_day5;
-use crate::day6::solve_day6;
-use crate::day7::solve_day7;
-use crate::day8::solve_day8;
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-
-fn main() {
-    /* Day 1 */
-    solve_day1();
-
-    /* Day 2 */
-    solve_day2();
-
-    /* Day 3 */
-    solve_day3();
-
-    /* Day 4 */
-    solve_day4();
-
-    /* Day 5 */
-    solve_day5();
-
-    /* Day 6 */
-    solve_day6();
-
-    /* Day 7 */
-    solve_day7();
-
-    /* Day 8 */
-    solve_day8();
+use std::process::ExitCode;
+use clap::Parser;
+use aoc2023::cli::{Cli, Command, RunArgs};
+use aoc2023::commands::{bench, day1, day2, day3, new_day, run, run_parallel, verify};
+use aoc2023::input::Inputs;
+use aoc2023::registry;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let registry = registry();
+    let inputs = Inputs::new(&cli.inputs);
+
+    let result = match cli.command {
+        Some(Command::Run(args)) if args.bench => bench(&registry, &inputs, &args),
+        Some(Command::Run(args)) if args.parallel => run_parallel(&registry, &inputs, &args),
+        Some(Command::Run(args)) => run(&registry, &inputs, &args),
+        Some(Command::Verify(args)) => verify(&registry, &inputs, &args),
+        Some(Command::NewDay(args)) => new_day(&inputs, &args),
+        Some(Command::Day1(args)) => day1(&inputs, &args),
+        Some(Command::Day2(args)) => day2(&inputs, &args),
+        Some(Command::Day3(args)) => day3(&inputs, &args),
+        None => run(&registry, &inputs, &RunArgs::all()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+use clap::ValueEnum;
+use serde::Serialize;
+use crate::solution::{Answer, Part};
+
+/// How `run` prints its results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// `Day 1 Part 1 Solution: 56108`, with a blank line after each day.
+    #[default]
+    Text,
+    /// A JSON array with one object per part.
+    Json,
+    /// A header line and one row per part.
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Solved,
+    /// The part isn't run, see [`crate::solution::Solution::part2`].
+    Skipped,
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Solved => write!(f, "solved"),
+            Status::Skipped => write!(f, "skipped"),
+            Status::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// The result of solving one part of one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub day: u32,
+    pub part: Part,
+    pub answer: Result<Option<Answer>, String>,
+    /// Time spent on this part, including parsing when this part was the one to parse.
+    pub time: Duration,
+}
+
+impl Outcome {
+    pub fn status(&self) -> Status {
+        match self.answer {
+            Ok(Some(_)) => Status::Solved,
+            Ok(None) => Status::Skipped,
+            Err(_) => Status::Failed,
+        }
+    }
+}
+
+/// One [`Outcome`] as it appears in JSON and CSV.
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+    day: u32,
+    part: u32,
+    status: Status,
+    answer: Option<Answer>,
+    time_ns: u64,
+    error: Option<&'a str>,
+}
+
+impl<'a> From<&'a Outcome> for Record<'a> {
+    fn from(outcome: &'a Outcome) -> Self {
+        Record {
+            day: outcome.day,
+            part: outcome.part.number(),
+            status: outcome.status(),
+            answer: outcome.answer.as_ref().ok().copied().flatten(),
+            time_ns: outcome.time.as_nanos().try_into().unwrap_or(u64::MAX),
+            error: outcome.answer.as_ref().err().map(String::as_str),
+        }
+    }
+}
+
+/// Quotes a CSV field when it has to be.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+pub fn write_outcomes(out: &mut impl Write, outcomes: &[Outcome], format: Format) -> io::Result<()> {
+    match format {
+        Format::Text => {
+            let mut days = outcomes.iter().peekable();
+            while let Some(outcome) = days.next() {
+                match &outcome.answer {
+                    Ok(Some(answer)) => writeln!(out, "Day {} Part {} Solution: {}", outcome.day, outcome.part, answer)?,
+                    Ok(None) => {}
+                    Err(e) => writeln!(out, "Day {} Part {} Failed: {}", outcome.day, outcome.part, e)?,
+                }
+                if days.peek().is_none_or(|next| next.day != outcome.day) {
+                    writeln!(out)?;
+                }
+            }
+        }
+        Format::Json => {
+            let records = outcomes.iter().map(Record::from).collect::<Vec<_>>();
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)?;
+        }
+        Format::Csv => {
+            writeln!(out, "day,part,status,answer,time_ns,error")?;
+            for record in outcomes.iter().map(Record::from) {
+                writeln!(out, "{},{},{},{},{},{}",
+                         record.day,
+                         record.part,
+                         record.status,
+                         record.answer.map(|a| a.to_string()).unwrap_or_default(),
+                         record.time_ns,
+                         csv_field(record.error.unwrap_or_default()))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+    use crate::output::{write_outcomes, Format, Outcome};
+    use crate::solution::Part;
+
+    fn outcomes() -> Vec<Outcome> {
+        vec![
+            Outcome { day: 5, part: Part::One, answer: Ok(Some(35)), time: Duration::from_nanos(120) },
+            Outcome { day: 5, part: Part::Two, answer: Ok(None), time: Duration::from_nanos(3) },
+            Outcome { day: 7, part: Part::One, answer: Err(String::from("day 7: \"AAAA\", too short")), time: Duration::ZERO },
+        ]
+    }
+
+    fn written(format: Format) -> String {
+        let mut out = vec!();
+        write_outcomes(&mut out, &outcomes(), format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(written(Format::Text),
+                   "Day 5 Part 1 Solution: 35\n\nDay 7 Part 1 Failed: day 7: \"AAAA\", too short\n\n");
+    }
+
+    #[test]
+    fn test_csv_and_json() {
+        assert_eq!(written(Format::Csv), "day,part,status,answer,time_ns,error\n\
+                                          5,1,solved,35,120,\n\
+                                          5,2,skipped,,3,\n\
+                                          7,1,failed,,0,\"day 7: \"\"AAAA\"\", too short\"\n");
+
+        let json: serde_json::Value = serde_json::from_str(&written(Format::Json)).unwrap();
+        assert_eq!(json[0], serde_json::json!({
+            "day": 5, "part": 1, "status": "solved", "answer": 35, "time_ns": 120, "error": null,
+        }));
+        assert_eq!(json[2]["status"], "failed");
+    }
+}
@@ -0,0 +1,245 @@
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::output::Outcome;
+use crate::solution::{Parsed, Part, Runner};
+
+#[derive(Debug)]
+pub struct Summary {
+    /// In the order the days and parts were given, however the threads happened to finish.
+    pub outcomes: Vec<Outcome>,
+    pub wall: Duration,
+    pub threads: usize,
+}
+
+impl Summary {
+    /// The time every part spent on its thread, added up.
+    pub fn cpu(&self) -> Duration {
+        self.outcomes.iter().map(|o| o.time).sum()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.answer.is_err()).count()
+    }
+}
+
+/// A day and the text to solve each of its parts with.
+pub type DayTexts<'a> = (&'a dyn Runner, Vec<(Part, String)>);
+
+/// Parts of the same day that read the same text share one parse, done by whichever gets there first.
+struct Job<'a> {
+    day: &'a dyn Runner,
+    part: Part,
+    text: &'a str,
+    parsed: &'a OnceLock<Result<Parsed, String>>,
+}
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let message = payload.downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    format!("panicked: {message}")
+}
+
+fn solve(job: &Job) -> Outcome {
+    /* Only the thread that parses counts the parse, waiting on another thread's isn't work done by this one */
+    let mut time = Duration::ZERO;
+    let parsed = job.parsed.get_or_init(|| {
+        let start = Instant::now();
+        let parsed = panic::catch_unwind(AssertUnwindSafe(|| job.day.parse(job.text)))
+            .map_err(panic_message)
+            .and_then(|r| r.map_err(|e| e.to_string()))
+            .map_err(|e| format!("day {}: {e}", job.day.day()));
+        time = start.elapsed();
+        parsed
+    });
+
+    let answer = match parsed {
+        Ok(input) => {
+            let start = Instant::now();
+            let answer = panic::catch_unwind(AssertUnwindSafe(|| job.day.solve(input.as_ref(), job.part)))
+                .map_err(panic_message)
+                .and_then(|r| r.map_err(|e| e.to_string()))
+                .map_err(|e| format!("day {} part {}: {e}", job.day.day(), job.part));
+            time += start.elapsed();
+            answer
+        }
+        Err(e) => Err(e.clone()),
+    };
+
+    Outcome { day: job.day.day(), part: job.part, answer, time }
+}
+
+/// Solves every part of every day on a pool of `threads` workers. A day that fails or panics
+/// only fails its own parts.
+pub fn solve_all(days: &[DayTexts], threads: usize) -> Summary {
+    let threads = threads.max(1);
+    let start = Instant::now();
+
+    let parses = days.iter()
+        .flat_map(|(_, texts)| texts.iter().map(|_| OnceLock::new()))
+        .collect::<Vec<_>>();
+
+    let mut jobs = vec!();
+    let mut slot = 0;
+    for (day, texts) in days {
+        for (i, (part, text)) in texts.iter().enumerate() {
+            /* Reuse the parse of an earlier part with the same text */
+            let shared = texts[..i].iter().position(|(_, prev)| prev == text);
+            let parsed = &parses[slot + shared.unwrap_or(i)];
+            jobs.push(Job { day: *day, part: *part, text, parsed });
+        }
+        slot += texts.len();
+    }
+
+    let next = AtomicUsize::new(0);
+    let outcomes = jobs.iter().map(|_| Mutex::new(None)).collect::<Vec<_>>();
+    thread::scope(|scope| {
+        for _ in 0..threads.min(jobs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(i) else {
+                        break;
+                    };
+                    *outcomes[i].lock().unwrap() = Some(solve(job));
+                }
+            });
+        }
+    });
+
+    let outcomes = outcomes.into_iter()
+        .map(|o| o.into_inner().unwrap().expect("every job is run"))
+        .collect();
+
+    Summary { outcomes, wall: start.elapsed(), threads }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt;
+    use std::thread;
+    use std::time::Duration;
+    use crate::parallel::solve_all;
+    use crate::solution::{Answer, Part, Registry, Solution};
+
+    #[derive(Debug)]
+    struct Broken;
+
+    impl fmt::Display for Broken {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "broken")
+        }
+    }
+
+    impl std::error::Error for Broken {}
+
+    struct Length;
+
+    impl Solution for Length {
+        const DAY: u32 = 1;
+        type Input = usize;
+        type Error = Broken;
+
+        fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+            Ok(text.len())
+        }
+
+        fn part1(input: &Self::Input) -> Result<Answer, Self::Error> {
+            Ok(*input as Answer)
+        }
+
+        fn part2(input: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+            Ok(Some(2 * *input as Answer))
+        }
+    }
+
+    struct Panics;
+
+    impl Solution for Panics {
+        const DAY: u32 = 2;
+        type Input = ();
+        type Error = Broken;
+
+        fn parse(_: &str) -> Result<Self::Input, Self::Error> {
+            Ok(())
+        }
+
+        fn part1(_: &Self::Input) -> Result<Answer, Self::Error> {
+            panic!("oh no")
+        }
+
+        fn part2(_: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+            Err(Broken)
+        }
+    }
+
+    struct SlowParse;
+
+    impl Solution for SlowParse {
+        const DAY: u32 = 3;
+        type Input = ();
+        type Error = Broken;
+
+        fn parse(_: &str) -> Result<Self::Input, Self::Error> {
+            thread::sleep(Duration::from_millis(100));
+            Ok(())
+        }
+
+        fn part1(_: &Self::Input) -> Result<Answer, Self::Error> {
+            Ok(1)
+        }
+
+        fn part2(_: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+            Ok(Some(2))
+        }
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let mut registry = Registry::new();
+        registry.register::<Length>().register::<Panics>();
+        let length = registry.get(1).unwrap();
+        let panics = registry.get(2).unwrap();
+
+        let both = |text: &str| vec![(Part::One, String::from(text)), (Part::Two, String::from(text))];
+        let days = vec![(panics, both("")), (length, both("abc"))];
+        let summary = solve_all(&days, 4);
+
+        let answers = summary.outcomes.iter()
+            .map(|o| (o.day, o.part, o.answer.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(answers, vec![
+            (2, Part::One, Err(String::from("day 2 part 1: panicked: oh no"))),
+            (2, Part::Two, Err(String::from("day 2 part 2: broken"))),
+            (1, Part::One, Ok(Some(3))),
+            (1, Part::Two, Ok(Some(6))),
+        ]);
+        assert_eq!(summary.failures(), 2);
+    }
+
+    #[test]
+    fn test_shared_parse_time() {
+        let mut registry = Registry::new();
+        registry.register::<SlowParse>();
+        let texts = vec![(Part::One, String::new()), (Part::Two, String::new())];
+        let summary = solve_all(&[(registry.get(3).unwrap(), texts)], 2);
+
+        /* The part that waited on the other's parse didn't do it again */
+        let mut times = summary.outcomes.iter().map(|o| o.time).collect::<Vec<_>>();
+        times.sort();
+        assert!(times[0] < Duration::from_millis(50), "{times:?}");
+        assert!(times[1] >= Duration::from_millis(100), "{times:?}");
+    }
+}
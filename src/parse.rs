@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A malformed input, pointing at the first character that didn't match. Lines and columns start at 1.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("line {line}, column {column}: expected {expected}, found {found}")]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: &'static str,
+    pub found: String,
+}
+
+/// A single line of puzzle input that knows where it came from, so errors can say where they happened.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+/// Numbered lines of the input.
+pub fn lines(text: &str) -> impl Iterator<Item = Line<'_>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, text)| Line { number: i + 1, text })
+}
+
+impl<'a> Line<'a> {
+    /// Column of `token`, which must be a slice of this line. Anything else is reported as the end of the line.
+    pub fn column(&self, token: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (token.as_ptr() as usize).wrapping_sub(start);
+        if offset > self.text.len() {
+            return self.text.chars().count() + 1;
+        }
+        self.text[..offset].chars().count() + 1
+    }
+
+    /// Error pointing at `token`.
+    pub fn error(&self, token: &str, expected: &'static str) -> ParseError {
+        let found = if token.is_empty() {
+            String::from("end of line")
+        } else {
+            format!("{token:?}")
+        };
+        ParseError { line: self.number, column: self.column(token), expected, found }
+    }
+
+    /// Error for a line that stopped short.
+    pub fn error_at_end(&self, expected: &'static str) -> ParseError {
+        self.error(&self.text[self.text.len()..], expected)
+    }
+
+    /// Splits `within` (a slice of this line) around the first `separator`.
+    pub fn split_once(&self, within: &'a str, separator: &'static str, expected: &'static str) -> Result<(&'a str, &'a str), ParseError> {
+        within.split_once(separator)
+            .ok_or_else(|| self.error(&within[within.len()..], expected))
+    }
+
+    /// Parses a whole token, e.g. a number.
+    pub fn parse<T: FromStr>(&self, token: &'a str, expected: &'static str) -> Result<T, ParseError> {
+        token.parse::<T>()
+            .map_err(|_| self.error(token, expected))
+    }
+
+    /// Parses every whitespace separated token in `within`.
+    pub fn parse_all<T: FromStr>(&self, within: &'a str, expected: &'static str) -> Result<Vec<T>, ParseError> {
+        within.split_whitespace()
+            .map(|token| self.parse(token, expected))
+            .collect()
+    }
+}
+
+/// Reads a file mapping names to numbers, as a TOML table (`red = 12`) or a JSON object (`{"red": 12}`)
+/// depending on the extension, with `what` saying what the file holds. Fails with the reason, for the
+/// caller to report with the path.
+pub fn load_table(path: &Path, what: &str) -> Result<BTreeMap<String, u32>, String> {
+    let text = fs::read_to_string(path).map_err(|e| { e.to_string() })?;
+    match path.extension().and_then(|e| { e.to_str() }) {
+        Some("toml") => toml::from_str(&text).map_err(|e| { e.message().to_string() }),
+        Some("json") => serde_json::from_str(&text).map_err(|e| { e.to_string() }),
+        _ => Err(format!("expected a .toml or .json {what}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse::{lines, ParseError};
+
+    #[test]
+    fn test_positions() {
+        let text = "Card 1: 41 48\nCard 2: 13 x2";
+        let line = lines(text).nth(1).unwrap();
+        let (_, nums) = line.split_once(line.text, ":", "':'").unwrap();
+
+        assert_eq!(line.parse_all::<u32>(nums, "a number"), Err(ParseError {
+            line: 2,
+            column: 12,
+            expected: "a number",
+            found: String::from("\"x2\""),
+        }));
+
+        let err = line.split_once(nums, "|", "'|'").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 14));
+        assert_eq!(err.to_string(), "line 2, column 14: expected '|', found end of line");
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use crate::input::{Inputs, DEFAULT_INPUTS};
+
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    #[error("day {day} already exists: {}", .path.display())]
+    Exists { day: u32, path: PathBuf },
+    #[error("unable to access {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{} has no {what} to add the day after", .path.display())]
+    NoAnchor { path: PathBuf, what: &'static str },
+}
+
+/// The source of a new day, with a solution that parses lines and answers 0 until it's filled in.
+pub fn module(day: u32) -> String {
+    format!(r#"use thiserror::Error;
+use crate::parse::{{lines, ParseError}};
+use crate::solution::{{Answer, Solution}};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Day{day}Error {{
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}}
+
+pub struct Day{day};
+
+impl Solution for Day{day} {{
+    const DAY: u32 = {day};
+    type Input = Vec<String>;
+    type Error = Day{day}Error;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error> {{
+        Ok(lines(text).map(|line| {{ String::from(line.text) }}).collect())
+    }}
+
+    fn part1(input: &Self::Input) -> Result<Answer, Self::Error> {{
+        Ok(solve_part1(input) as Answer)
+    }}
+
+    fn part2(input: &Self::Input) -> Result<Option<Answer>, Self::Error> {{
+        Ok(Some(solve_part2(input) as Answer))
+    }}
+}}
+
+pub fn solve_part1(_lines: &[String]) -> u32 {{
+    0
+}}
+
+pub fn solve_part2(_lines: &[String]) -> u32 {{
+    0
+}}
+
+#[cfg(test)]
+mod test {{
+    use crate::day{day}::{{solve_part1, solve_part2, Day{day}}};
+    use crate::solution::Solution;
+
+    fn read_day{day}_test_file() -> String {{
+        String::from(include_str!("../inputs/day{day}_test.txt"))
+    }}
+
+    #[test]
+    fn test_part1() {{
+        assert_eq!(solve_part1(&Day{day}::parse(&read_day{day}_test_file()).unwrap()), 0)
+    }}
+
+    #[test]
+    fn test_part2() {{
+        assert_eq!(solve_part2(&Day{day}::parse(&read_day{day}_test_file()).unwrap()), 0)
+    }}
+}}
+"#)
+}
+
+/// The day number of a line like `pub mod day7;` or `.register::<day7::Day7>()`.
+fn day_of(line: &str, prefix: &str) -> Option<u32> {
+    let rest = line.trim().strip_prefix(prefix)?;
+    let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// Inserts `line` among the lines `prefix` numbers, keeping them ordered by day.
+fn insert_ordered(lines: &mut Vec<String>, prefix: &str, day: u32, line: impl Fn(&str) -> String) -> Option<usize> {
+    let numbered = lines.iter()
+        .enumerate()
+        .filter_map(|(i, l)| { day_of(l, prefix).map(|d| (i, d)) })
+        .collect::<Vec<_>>();
+    let (last, _) = *numbered.last()?;
+    let at = numbered.iter()
+        .find(|(_, d)| *d > day)
+        .map_or(last + 1, |(i, _)| *i);
+
+    let template = &lines[at.min(last)];
+    let indent = &template[..template.len() - template.trim_start().len()];
+    lines.insert(at, line(indent));
+    Some(at)
+}
+
+/// Adds the `pub mod` and the registry entry for `day` to the text of `lib.rs`.
+pub fn add_to_lib(lib: &str, day: u32) -> Option<String> {
+    let mut lines = lib.lines().map(String::from).collect::<Vec<_>>();
+    insert_ordered(&mut lines, "pub mod day", day, |indent| format!("{indent}pub mod day{day};"))?;
+
+    let at = insert_ordered(&mut lines, ".register::<day", day, |indent| format!("{indent}.register::<day{day}::Day{day}>()"))?;
+    /* The chain ends on the last registration */
+    if at > 0 && lines[at - 1].ends_with(';') && day_of(&lines[at - 1], ".register::<day").is_some() {
+        lines[at - 1].pop();
+        lines[at].push(';');
+    }
+
+    Some(lines.join("\n") + "\n")
+}
+
+/// Adds a status row without any stars yet for `day` to the README.
+pub fn add_to_readme(readme: &str, day: u32) -> Option<String> {
+    let mut lines = readme.lines().map(String::from).collect::<Vec<_>>();
+    insert_ordered(&mut lines, "| ", day, |_| format!("| {day:<3} |      |"))?;
+    Some(lines.join("\n") + "\n")
+}
+
+fn read(path: &Path) -> Result<String, ScaffoldError> {
+    fs::read_to_string(path)
+        .map_err(|source| ScaffoldError::Io { path: path.to_path_buf(), source })
+}
+
+fn write(path: &Path, text: &str) -> Result<(), ScaffoldError> {
+    fs::write(path, text)
+        .map_err(|source| ScaffoldError::Io { path: path.to_path_buf(), source })
+}
+
+/// Adds `day` to the crate at `root`, with an empty puzzle input in `inputs`. Nothing is written if the day
+/// exists in any form. Returns the files created or changed.
+pub fn new_day(root: &Path, inputs: &Inputs, day: u32) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let module_path = root.join("src").join(format!("day{day}.rs"));
+    let lib_path = root.join("src").join("lib.rs");
+    let readme_path = root.join("README.md");
+    /* The tests include the example from the crate, wherever the puzzle inputs live */
+    let example_path = root.join(DEFAULT_INPUTS).join(format!("day{day}_test.txt"));
+    let puzzle_path = inputs.puzzle_path(day);
+
+    for path in [&module_path, &example_path, &puzzle_path] {
+        if path.exists() {
+            return Err(ScaffoldError::Exists { day, path: path.clone() });
+        }
+    }
+
+    let lib = read(&lib_path)?;
+    if lib.lines().any(|l| { day_of(l, "pub mod day") == Some(day) }) {
+        return Err(ScaffoldError::Exists { day, path: lib_path });
+    }
+    let lib = add_to_lib(&lib, day)
+        .ok_or(ScaffoldError::NoAnchor { path: lib_path.clone(), what: "day modules" })?;
+    let readme = add_to_readme(&read(&readme_path)?, day)
+        .ok_or(ScaffoldError::NoAnchor { path: readme_path.clone(), what: "status table" })?;
+
+    write(&module_path, &module(day))?;
+    write(&example_path, "")?;
+    write(&puzzle_path, "")?;
+    write(&lib_path, &lib)?;
+    write(&readme_path, &readme)?;
+
+    Ok(vec![module_path, example_path, puzzle_path, lib_path, readme_path])
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use crate::input::Inputs;
+    use crate::scaffold::{add_to_lib, add_to_readme, new_day, ScaffoldError};
+    use crate::testing::temp_dir;
+
+    const LIB: &str = "pub mod solution;\npub mod day1;\npub mod day3;\n\n\
+                       pub fn registry() -> Registry {\n    \
+                           registry\n        \
+                               .register::<day1::Day1>()\n        \
+                               .register::<day3::Day3>();\n    \
+                           registry\n}\n";
+
+    #[test]
+    fn test_edits() {
+        assert_eq!(add_to_lib(LIB, 2).unwrap(),
+                   "pub mod solution;\npub mod day1;\npub mod day2;\npub mod day3;\n\n\
+                    pub fn registry() -> Registry {\n    \
+                        registry\n        \
+                            .register::<day1::Day1>()\n        \
+                            .register::<day2::Day2>()\n        \
+                            .register::<day3::Day3>();\n    \
+                        registry\n}\n");
+        assert!(add_to_lib(LIB, 4).unwrap().contains(".register::<day3::Day3>()\n        .register::<day4::Day4>();\n"));
+        assert_eq!(add_to_lib("pub mod solution;\n", 1), None);
+
+        let readme = "| Day | Result |\n|-----| ---    |\n| 1   | ⭐⭐  |\n\n## Usage\n";
+        assert_eq!(add_to_readme(readme, 2).unwrap(),
+                   "| Day | Result |\n|-----| ---    |\n| 1   | ⭐⭐  |\n| 2   |      |\n\n## Usage\n");
+    }
+
+    #[test]
+    fn test_new_day() {
+        let root = temp_dir("scaffold");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("inputs")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        fs::write(root.join("README.md"), "| 1   | ⭐⭐  |\n").unwrap();
+
+        let inputs = Inputs::new(root.join("inputs"));
+        let written = new_day(&root, &inputs, 4).unwrap();
+        assert_eq!(written.len(), 5);
+        assert!(fs::read_to_string(root.join("src/day4.rs")).unwrap().contains("impl Solution for Day4"));
+        assert_eq!(fs::read_to_string(root.join("inputs/day4_test.txt")).unwrap(), "");
+
+        assert!(matches!(new_day(&root, &inputs, 4), Err(ScaffoldError::Exists { day: 4, .. })));
+        assert!(matches!(new_day(&root, &inputs, 1), Err(ScaffoldError::Exists { day: 1, .. })));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use clap::ValueEnum;
+
+/// Every answer in this year's puzzles fits in a `u64`.
+pub type Answer = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Part {
+    #[value(name = "1")]
+    One,
+    #[value(name = "2")]
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u32 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// A single day of the puzzle. The input is parsed once and shared by both parts.
+pub trait Solution {
+    const DAY: u32;
+    type Input;
+    type Error: Error + 'static;
+
+    fn parse(text: &str) -> Result<Self::Input, Self::Error>;
+    fn part1(input: &Self::Input) -> Result<Answer, Self::Error>;
+    /// `None` when part 2 is not run, e.g. a brute force that takes too long on the real input.
+    fn part2(input: &Self::Input) -> Result<Option<Answer>, Self::Error>;
+}
+
+/// A parsed input of any day, which can be shared between threads.
+pub type Parsed = Box<dyn Any + Send + Sync>;
+
+/// Type erased view of a [`Solution`] so different days can live in the same [`Registry`].
+pub trait Runner: Send + Sync {
+    fn day(&self) -> u32;
+    fn parse(&self, text: &str) -> Result<Parsed, Box<dyn Error>>;
+    fn part1(&self, input: &dyn Any) -> Result<Answer, Box<dyn Error>>;
+    fn part2(&self, input: &dyn Any) -> Result<Option<Answer>, Box<dyn Error>>;
+
+    fn solve(&self, input: &dyn Any, part: Part) -> Result<Option<Answer>, Box<dyn Error>> {
+        match part {
+            Part::One => self.part1(input).map(Some),
+            Part::Two => self.part2(input),
+        }
+    }
+}
+
+struct Entry<S>(PhantomData<fn() -> S>);
+
+impl<S: Solution> Entry<S> {
+    fn input_of<'a>(&self, input: &'a dyn Any) -> &'a S::Input
+    where
+        S::Input: 'static,
+    {
+        input.downcast_ref::<S::Input>()
+            .expect("input was parsed by a different day")
+    }
+}
+
+impl<S: Solution> Runner for Entry<S>
+where
+    S::Input: Send + Sync + 'static,
+{
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn parse(&self, text: &str) -> Result<Parsed, Box<dyn Error>> {
+        let input = S::parse(text)?;
+        Ok(Box::new(input))
+    }
+
+    fn part1(&self, input: &dyn Any) -> Result<Answer, Box<dyn Error>> {
+        Ok(S::part1(self.input_of(input))?)
+    }
+
+    fn part2(&self, input: &dyn Any) -> Result<Option<Answer>, Box<dyn Error>> {
+        Ok(S::part2(self.input_of(input))?)
+    }
+}
+
+/// All the days we have solutions for, kept in day order.
+#[derive(Default)]
+pub struct Registry {
+    days: Vec<Box<dyn Runner>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn register<S>(&mut self) -> &mut Self
+    where
+        S: Solution + 'static,
+        S::Input: Send + Sync + 'static,
+    {
+        assert!(self.get(S::DAY).is_none(), "day {} registered twice", S::DAY);
+        self.days.push(Box::new(Entry::<S>(PhantomData)));
+        self.days.sort_by_key(|d| d.day());
+        self
+    }
+
+    pub fn get(&self, day: u32) -> Option<&dyn Runner> {
+        self.days.iter()
+            .find(|d| d.day() == day)
+            .map(|d| d.as_ref())
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &dyn Runner> {
+        self.days.iter().map(|d| d.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::ParseIntError;
+    use crate::solution::{Answer, Registry, Solution};
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: u32 = 2;
+        type Input = Vec<u64>;
+        type Error = ParseIntError;
+
+        fn parse(text: &str) -> Result<Self::Input, Self::Error> {
+            text.lines().map(|l| l.parse()).collect()
+        }
+
+        fn part1(input: &Self::Input) -> Result<Answer, Self::Error> {
+            Ok(input.iter().sum())
+        }
+
+        fn part2(input: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+            Ok(Some(input.iter().product()))
+        }
+    }
+
+    struct Empty;
+
+    impl Solution for Empty {
+        const DAY: u32 = 1;
+        type Input = ();
+        type Error = ParseIntError;
+
+        fn parse(_: &str) -> Result<Self::Input, Self::Error> {
+            Ok(())
+        }
+
+        fn part1(_: &Self::Input) -> Result<Answer, Self::Error> {
+            Ok(0)
+        }
+
+        fn part2(_: &Self::Input) -> Result<Option<Answer>, Self::Error> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut registry = Registry::new();
+        registry.register::<Sum>().register::<Empty>();
+
+        let days = registry.days().map(|d| d.day()).collect::<Vec<_>>();
+        assert_eq!(days, vec![1, 2]);
+
+        let sum = registry.get(2).unwrap();
+        let input = sum.parse("1\n2\n3").unwrap();
+        assert_eq!(sum.part1(input.as_ref()).unwrap(), 6);
+        assert_eq!(sum.part2(input.as_ref()).unwrap(), Some(6));
+        assert!(sum.parse("1\nx").is_err());
+        assert!(registry.get(3).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_day() {
+        Registry::new().register::<Sum>().register::<Sum>();
+    }
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+use aoc2023::answers::{Answers, Status};
+use aoc2023::input::Inputs;
+use aoc2023::registry;
+use aoc2023::solution::Part;
+
+fn inputs() -> Inputs {
+    Inputs::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("inputs"))
+}
+
+#[test]
+fn test_examples() {
+    let inputs = inputs();
+    for day in registry().days() {
+        for part in Part::ALL {
+            let text = inputs.example(day.day(), part).unwrap();
+            /* A new day's example stays empty until it's filled in */
+            if text.trim().is_empty() {
+                continue;
+            }
+            let input = day.parse(&text).unwrap();
+            let expected = Answers::load(&inputs.example_path(day.day(), part)).unwrap().get(part);
+
+            let status = Status::check(expected, day.solve(input.as_ref(), part).unwrap());
+            assert!(matches!(status, Status::Pass | Status::Skipped), "day {} part {part}: {status}", day.day());
+        }
+    }
+}
+
+#[test]
+fn test_registry() {
+    let registry = registry();
+    let days = registry.days().map(|d| d.day()).collect::<Vec<_>>();
+    assert!(days.windows(2).all(|w| w[0] < w[1]), "days out of order: {days:?}");
+    assert!(days.iter().all(|d| registry.get(*d).is_some()));
+
+    let day = registry.get(4).unwrap();
+    assert!(day.parse("Card 1: 41 48 83 86 17").is_err());
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::PathBuf;
+use aoc2023::day3::{Point, Schematic};
+use aoc2023::day5::Garden;
+use aoc2023::day7::{CamelCards, CamelCards2};
+use aoc2023::day8::{self, Desert};
+
+fn read_example(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("inputs").join(name);
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn test_garden() {
+    let garden: Garden = read_example("day5_test.txt").parse().unwrap();
+    assert_eq!(garden.seeds, vec![79, 14, 55, 13]);
+    assert_eq!(garden.get_locations_for_seeds(), vec![82, 43, 86, 35]);
+}
+
+#[test]
+fn test_camel_cards() {
+    let text = read_example("day7_test.txt");
+    let cards: CamelCards = text.parse().unwrap();
+    let ranked = cards.hands.iter().map(|h| h.hand.as_str()).collect::<Vec<_>>();
+    assert_eq!(ranked, vec!["32T3K", "KTJJT", "KK677", "T55J5", "QQQJA"]);
+
+    let cards: CamelCards2 = text.parse().unwrap();
+    assert_eq!(cards.hands.last().unwrap().hand, "KTJJT");
+}
+
+#[test]
+fn test_desert() {
+    let desert: Desert = read_example("day8_test_2.txt").parse().unwrap();
+    assert_eq!(desert.path, "LLR");
+    assert_eq!(desert.get_next_dir(4), 'L');
+    assert_eq!(day8::solve_part1(&desert), Ok(6));
+}
+
+#[test]
+fn test_schematic_helpers() {
+    let schematic: Schematic = read_example("day3_test.txt").parse().unwrap();
+    assert_eq!(schematic.number_at(Point { x: 1, y: 0 }).map(|n| n.value), Some(467));
+    assert_eq!(schematic.number_at(Point { x: 3, y: 1 }), None);
+
+    let corner = schematic.grid.neighbours8(Point { x: 0, y: 0 }).collect::<Vec<_>>();
+    assert_eq!(corner.len(), 3);
+    assert!(corner.contains(&Point { x: 1, y: 1 }));
+    assert_eq!(schematic.grid.neighbours8(Point { x: 9, y: 9 }).count(), 3);
+}